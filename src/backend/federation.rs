@@ -0,0 +1,86 @@
+use reqwest::{Method, StatusCode};
+use serde::Deserialize;
+
+use super::{enc, enc_query, EmptyV1, Synapse};
+
+impl Synapse {
+    /// Lists the servers we federated with, only the ones whose name contains
+    /// `search` if not empty
+    pub fn list_destinations(
+        &mut self,
+        offset: usize,
+        page_size: usize,
+        search: &str,
+    ) -> Result<ListDestinationsV1, String> {
+        let mut url = format!(
+            "_synapse/admin/v1/federation/destinations?from={}&limit={}",
+            offset, page_size
+        );
+        if !search.is_empty() {
+            url.push_str("&destination=");
+            url.push_str(enc_query(search).as_str());
+        }
+        self.send_json::<_, (), _>(Method::GET, url, None, Some(StatusCode::OK))
+    }
+
+    /// Lists the rooms shared with a destination
+    pub fn destination_rooms(
+        &mut self,
+        destination: &str,
+        offset: usize,
+        page_size: usize,
+    ) -> Result<DestinationRoomsV1, String> {
+        let url = format!(
+            "_synapse/admin/v1/federation/destinations/{}/rooms?from={}&limit={}",
+            enc(destination),
+            offset,
+            page_size
+        );
+        self.send_json::<_, (), _>(Method::GET, url, None, Some(StatusCode::OK))
+    }
+
+    /// Clears the backoff of a destination so the next transaction is sent right away
+    pub fn reset_destination_connection(&mut self, destination: &str) -> Result<(), String> {
+        let url = format!(
+            "_synapse/admin/v1/federation/destinations/{}/reset_connection",
+            enc(destination)
+        );
+        self.send(Method::POST, url, Some(EmptyV1 {}), Some(StatusCode::OK))?;
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+pub struct ListDestinationsV1 {
+    pub destinations: Vec<DestinationV1>,
+    pub total: usize,
+    pub next_token: Option<String>,
+}
+
+#[derive(Default, Clone, Deserialize)]
+#[serde(default)]
+pub struct DestinationV1 {
+    pub destination: String,
+    /// When the last attempt to send to the destination failed
+    pub retry_last_ts: u64,
+    /// How long until the next attempt, 0 if the destination is reachable
+    pub retry_interval: u64,
+    /// When the destination started failing, None if it is not failing
+    pub failure_ts: Option<u64>,
+    pub last_successful_stream_ordering: Option<u64>,
+}
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+pub struct DestinationRoomsV1 {
+    pub rooms: Vec<DestinationRoomV1>,
+    pub total: usize,
+    pub next_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct DestinationRoomV1 {
+    pub room_id: String,
+    pub stream_ordering: u64,
+}
@@ -0,0 +1,290 @@
+use reqwest::{Method, StatusCode};
+use serde::Deserialize;
+
+use super::{enc, enc_query, EmptyV1, Synapse};
+
+impl Synapse {
+    /// Lists the media uploaded by a user, largest first if `by_size`, newest first otherwise
+    pub fn list_user_media(
+        &mut self,
+        user_id: &str,
+        offset: usize,
+        page_size: usize,
+        by_size: bool,
+    ) -> Result<ListUserMediaV1, String> {
+        let url = format!(
+            "_synapse/admin/v1/users/{}/media?from={}&limit={}&order_by={}&dir=b",
+            enc(user_id),
+            offset,
+            page_size,
+            if by_size {
+                "media_length"
+            } else {
+                "created_ts"
+            }
+        );
+        self.send_json::<_, (), _>(Method::GET, url, None, Some(StatusCode::OK))
+    }
+
+    /// Deletes a single local media
+    pub fn delete_media(&mut self, media_id: &str) -> Result<(), String> {
+        let url = format!(
+            "_synapse/admin/v1/media/{}/{}",
+            enc(self.server_name()),
+            enc(media_id)
+        );
+        self.send::<_, ()>(Method::DELETE, url, None, Some(StatusCode::OK))?;
+        Ok(())
+    }
+
+    /// Deletes every media uploaded by a user, a page at a time. Returns the number deleted
+    pub fn delete_user_media(&mut self, user_id: &str) -> Result<usize, String> {
+        const PAGE_SIZE: usize = 100;
+        let mut deleted = 0;
+        loop {
+            let url = format!(
+                "_synapse/admin/v1/users/{}/media?limit={}",
+                enc(user_id),
+                PAGE_SIZE
+            );
+            let r: DeletedMediaV1 =
+                self.send_json::<_, (), _>(Method::DELETE, url, None, Some(StatusCode::OK))?;
+            deleted += r.total;
+            if r.total < PAGE_SIZE {
+                return Ok(deleted);
+            }
+        }
+    }
+
+    /// Quarantines a media or lifts its quarantine
+    pub fn quarantine_media(
+        &mut self,
+        server_name: &str,
+        media_id: &str,
+        quarantine: bool,
+    ) -> Result<(), String> {
+        let url = format!(
+            "_synapse/admin/v1/media/{}/{}/{}",
+            if quarantine {
+                "quarantine"
+            } else {
+                "unquarantine"
+            },
+            enc(server_name),
+            enc(media_id)
+        );
+        self.send(Method::POST, url, Some(EmptyV1 {}), Some(StatusCode::OK))?;
+        Ok(())
+    }
+
+    /// Quarantines all the media of a room. Returns the number quarantined
+    pub fn quarantine_room_media(&mut self, room_id: &str) -> Result<usize, String> {
+        let url = format!("_synapse/admin/v1/room/{}/media/quarantine", enc(room_id));
+        let r: QuarantinedV1 =
+            self.send_json(Method::POST, url, Some(EmptyV1 {}), Some(StatusCode::OK))?;
+        Ok(r.num_quarantined)
+    }
+
+    /// Quarantines all the media uploaded by a user. Returns the number quarantined
+    pub fn quarantine_user_media(&mut self, user_id: &str) -> Result<usize, String> {
+        let url = format!("_synapse/admin/v1/user/{}/media/quarantine", enc(user_id));
+        let r: QuarantinedV1 =
+            self.send_json(Method::POST, url, Some(EmptyV1 {}), Some(StatusCode::OK))?;
+        Ok(r.num_quarantined)
+    }
+
+    /// Protects a local media from quarantine or removes the protection
+    pub fn protect_media(&mut self, media_id: &str, protect: bool) -> Result<(), String> {
+        let url = format!(
+            "_synapse/admin/v1/media/{}/{}",
+            if protect { "protect" } else { "unprotect" },
+            enc(media_id)
+        );
+        self.send(Method::POST, url, Some(EmptyV1 {}), Some(StatusCode::OK))?;
+        Ok(())
+    }
+
+    /// Deletes the cached remote media not accessed since `before_ts`. Returns the number deleted
+    pub fn purge_media_cache(&mut self, before_ts: u64) -> Result<usize, String> {
+        let url = format!(
+            "_synapse/admin/v1/purge_media_cache?before_ts={}",
+            before_ts
+        );
+        let r: PurgedMediaV1 =
+            self.send_json(Method::POST, url, Some(EmptyV1 {}), Some(StatusCode::OK))?;
+        Ok(r.deleted)
+    }
+
+    /// Deletes the local media matching `query`. Returns the number deleted
+    pub fn delete_old_media(&mut self, query: &OldMediaQuery) -> Result<usize, String> {
+        let url = format!(
+            "_synapse/admin/v1/media/delete?before_ts={}&size_gt={}&keep_profiles={}",
+            query.before_ts, query.size_gt, query.keep_profiles
+        );
+        let r: DeletedMediaV1 =
+            self.send_json(Method::POST, url, Some(EmptyV1 {}), Some(StatusCode::OK))?;
+        Ok(r.total)
+    }
+
+    /// Returns a page of the media usage of local users
+    pub fn user_media_stats(
+        &mut self,
+        offset: usize,
+        page_size: usize,
+        query: &MediaStatsQuery,
+    ) -> Result<UserMediaStatsV1, String> {
+        let mut url = format!(
+            "_synapse/admin/v1/statistics/users/media?from={}&limit={}",
+            offset, page_size
+        );
+        if let Some(ts) = query.from_ts {
+            url.push_str(&format!("&from_ts={}", ts));
+        }
+        if let Some(ts) = query.until_ts {
+            url.push_str(&format!("&until_ts={}", ts));
+        }
+        if !query.search_term.is_empty() {
+            url.push_str("&search_term=");
+            url.push_str(enc_query(query.search_term.as_str()).as_str());
+        }
+        if !query.order_by.is_empty() {
+            url.push_str("&order_by=");
+            url.push_str(query.order_by);
+            url.push_str(if query.descending { "&dir=b" } else { "&dir=f" });
+        }
+        self.send_json::<_, (), _>(Method::GET, url, None, Some(StatusCode::OK))
+    }
+
+    /// Sums the media usage of all local users matching `query` as (count, bytes)
+    pub fn total_media_usage(&mut self, query: &MediaStatsQuery) -> Result<(u64, u64), String> {
+        const PAGE_SIZE: usize = 100;
+        let (mut count, mut bytes) = (0, 0);
+        let mut offset = 0;
+        loop {
+            let page = self.user_media_stats(offset, PAGE_SIZE, query)?;
+            for u in page.users.iter() {
+                count += u.media_count;
+                bytes += u.media_length;
+            }
+            offset += page.users.len();
+            if page.users.len() < PAGE_SIZE {
+                return Ok((count, bytes));
+            }
+        }
+    }
+}
+
+/// Splits `mxc://server/media_id`, `server/media_id` or a bare local media id
+/// into (server name, media id)
+pub fn parse_mxc<'a>(input: &'a str, local_server: &'a str) -> Result<(&'a str, &'a str), String> {
+    let s = input.trim();
+    let s = s.strip_prefix("mxc://").unwrap_or(s);
+    let (server, media_id) = match s.split_once('/') {
+        Some((server, media_id)) => (server, media_id),
+        None => (local_server, s),
+    };
+    if server.is_empty() || media_id.is_empty() || media_id.contains('/') {
+        return Err(format!(
+            "Invalid media '{}', expected mxc://server/media_id",
+            input
+        ));
+    }
+    Ok((server, media_id))
+}
+
+#[derive(Default, Deserialize)]
+pub struct ListUserMediaV1 {
+    pub media: Vec<MediaInfoV1>,
+    /// Number of media of the user, not only in this page
+    pub total: usize,
+}
+
+#[derive(Default, Deserialize)]
+pub struct MediaInfoV1 {
+    pub media_id: String,
+    pub media_type: String,
+    pub media_length: u64,
+    pub upload_name: Option<String>,
+    pub created_ts: u64,
+    pub last_access_ts: Option<u64>,
+    pub quarantined_by: Option<String>,
+    pub safe_from_quarantine: bool,
+}
+
+#[allow(dead_code)]
+#[derive(Default, Deserialize)]
+struct DeletedMediaV1 {
+    deleted_media: Vec<String>,
+    total: usize,
+}
+
+/// Which local media gets deleted by [Synapse::delete_old_media]
+#[derive(Default, Clone)]
+pub struct OldMediaQuery {
+    /// Media last accessed before this
+    pub before_ts: u64,
+    /// Only media larger than this many bytes
+    pub size_gt: u64,
+    /// Keep media used as avatars
+    pub keep_profiles: bool,
+}
+
+/// Filters and ordering of the media statistics
+#[derive(Default, Clone)]
+pub struct MediaStatsQuery {
+    /// Only media uploaded after this
+    pub from_ts: Option<u64>,
+    /// Only media uploaded before this
+    pub until_ts: Option<u64>,
+    /// Only users whose id or display name contains this
+    pub search_term: String,
+    /// Column to sort on, server default if empty
+    pub order_by: &'static str,
+    pub descending: bool,
+}
+
+#[allow(dead_code)]
+#[derive(Default, Deserialize)]
+pub struct UserMediaStatsV1 {
+    pub users: Vec<UserMediaStatV1>,
+    pub total: usize,
+    pub next_token: Option<usize>,
+}
+
+#[derive(Default, Deserialize)]
+pub struct UserMediaStatV1 {
+    pub user_id: String,
+    pub displayname: Option<String>,
+    pub media_count: u64,
+    pub media_length: u64,
+}
+
+#[derive(Default, Deserialize)]
+struct PurgedMediaV1 {
+    deleted: usize,
+}
+
+#[derive(Default, Deserialize)]
+struct QuarantinedV1 {
+    num_quarantined: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_mxc_forms() {
+        assert_eq!(parse_mxc("mxc://s/id", "local"), Ok(("s", "id")));
+        assert_eq!(parse_mxc(" s/id ", "local"), Ok(("s", "id")));
+        assert_eq!(parse_mxc("id", "local"), Ok(("local", "id")));
+    }
+
+    #[test]
+    fn parse_mxc_invalid() {
+        assert!(parse_mxc("mxc://s/", "local").is_err());
+        assert!(parse_mxc("mxc:///id", "local").is_err());
+        assert!(parse_mxc("a/b/c", "local").is_err());
+        assert!(parse_mxc("", "local").is_err());
+    }
+}
@@ -1,10 +1,11 @@
-use std::{borrow::Cow, ops::Deref};
+use std::{borrow::Cow, collections::HashMap, ops::Deref};
 
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS, NON_ALPHANUMERIC};
 use reqwest::{
     blocking::{Client, Response},
     Method, StatusCode,
 };
-use serde::{Deserialize, Deserializer, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize};
 
 use crate::common::{
     editable::{Editable, EditableWidget},
@@ -12,6 +13,19 @@ use crate::common::{
     HandleRes,
 };
 
+mod federation;
+pub use federation::*;
+mod media;
+pub use media::*;
+mod registration_tokens;
+pub use registration_tokens::*;
+mod reports;
+pub use reports::*;
+mod rooms;
+pub use rooms::*;
+mod server_notices;
+pub use server_notices::*;
+
 pub trait BackendImpl {
     /// Returns true if the backend requires information from the user
     fn set_prompt(&self, _p: &mut Prompt) -> bool {
@@ -24,10 +38,44 @@ pub trait BackendImpl {
     }
 }
 
+/// Characters escaped when an identifier is used as a single url path segment
+const PATH_SEGMENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'/')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}');
+
+/// Characters escaped in a query value, everything but the unreserved ones
+const QUERY_COMPONENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+/// Escapes `s` so it can be used as a url path segment
+pub fn enc(s: &str) -> String {
+    utf8_percent_encode(s, PATH_SEGMENT).to_string()
+}
+
+/// Escapes `s` so it can be used as a query value
+pub fn enc_query(s: &str) -> String {
+    utf8_percent_encode(s, QUERY_COMPONENT).to_string()
+}
+
 pub struct Synapse {
     token_valid: bool,
     access_token: String,
     host: String,
+    server_name: String,
+    /// Whether users can log in with a password, fetched on first use
+    password_login: Option<bool>,
     client: Client,
     url_cache: String,
     body_cache: String,
@@ -96,6 +144,8 @@ impl Synapse {
         Self {
             token_valid: false,
             host,
+            server_name: String::new(),
+            password_login: None,
             access_token: String::new(),
             client: Client::builder()
                 .user_agent(format!(
@@ -132,35 +182,240 @@ impl Synapse {
             Err(_e) => return Err("Server response is invalid".into()),
         };
 
+        // Figure out the server name from our own user id
+        let me: WhoamiV3 = self.send_json::<_, (), _>(
+            Method::GET,
+            "_matrix/client/v3/account/whoami",
+            None,
+            Some(StatusCode::OK),
+        )?;
+        match me.user_id.split_once(':') {
+            Some((_, server_name)) => {
+                self.server_name.clear();
+                self.server_name.push_str(server_name);
+            }
+            None => return Err(format!("Invalid user id '{}'", me.user_id).into()),
+        }
+
         Ok(())
     }
 
+    /// The name of the server (the part after ':' in user ids)
+    pub fn server_name(&self) -> &str {
+        self.server_name.as_str()
+    }
+
+    /// Whether the server handles authentication with local passwords
+    pub fn password_login_enabled(&mut self) -> Result<bool, String> {
+        if let Some(v) = self.password_login {
+            return Ok(v);
+        }
+        let flows: LoginFlowsV3 = self.send_json::<_, (), _>(
+            Method::GET,
+            "_matrix/client/v3/login",
+            None,
+            Some(StatusCode::OK),
+        )?;
+        let v = flows.flows.iter().any(|f| f.r#type == "m.login.password");
+        self.password_login = Some(v);
+        Ok(v)
+    }
+
+    /// Builds a full user id from a localpart
+    pub fn user_id(&self, localpart: &str) -> String {
+        format!("@{}:{}", localpart, self.server_name)
+    }
+
     pub fn list_users(
         &mut self,
         offset: usize,
         page_size: usize,
+        filter: &UserFilter,
     ) -> Result<Vec<UserInfoV1>, String> {
+        // Deactivated accounts are kept when listing shadow-bans, they need a review too
+        let mut url = format!(
+            "_synapse/admin/v2/users?from={}&limit={}&guests={}&deactivated={}",
+            offset,
+            page_size,
+            filter.guests,
+            filter.deactivated || filter.shadow_banned
+        );
+        if !filter.name.is_empty() {
+            url.push_str("&name=");
+            url.push_str(enc_query(filter.name.as_str()).as_str());
+        }
+        if filter.shadow_banned {
+            url.push_str("&order_by=shadow_banned&dir=b");
+        }
+        let data: ListUserV1 =
+            self.send_json::<_, (), _>(Method::GET, url, None, Some(StatusCode::OK))?;
+
+        Ok(data.users)
+    }
+
+    /// Modifies the provided attributes of an existing user
+    pub fn modify_user(&mut self, user_id: &str, changes: &UserModifyV2) -> Result<(), String> {
+        let url = format!("_synapse/admin/v2/users/{}", enc(user_id));
+        self.send(Method::PUT, url, Some(changes), Some(StatusCode::OK))?;
+        Ok(())
+    }
+
+    /// Deactivates a user, optionally erasing its data
+    pub fn deactivate_user(&mut self, user_id: &str, erase: bool) -> Result<(), String> {
+        let url = format!("_synapse/admin/v1/deactivate/{}", enc(user_id));
+        self.send(
+            Method::POST,
+            url,
+            Some(DeactivateV1 { erase }),
+            Some(StatusCode::OK),
+        )?;
+        Ok(())
+    }
+
+    pub fn set_admin(&mut self, user_id: &str, admin: bool) -> Result<(), String> {
+        let url = format!("_synapse/admin/v1/users/{}/admin", enc(user_id));
+        self.send(
+            Method::PUT,
+            url,
+            Some(UserAdminV1 { admin }),
+            Some(StatusCode::OK),
+        )?;
+        Ok(())
+    }
+
+    /// Shadow-bans or lifts the shadow-ban of a user
+    pub fn set_shadow_banned(&mut self, user_id: &str, banned: bool) -> Result<(), String> {
+        let url = format!("_synapse/admin/v1/users/{}/shadow_ban", enc(user_id));
+        let method = if banned { Method::POST } else { Method::DELETE };
+        self.send::<_, ()>(method, url, None, Some(StatusCode::OK))?;
+        Ok(())
+    }
+
+    /// Removes any rate limit override of a user
+    pub fn reset_rate_limit(&mut self, user_id: &str) -> Result<(), String> {
+        let url = format!(
+            "_synapse/admin/v1/users/{}/override_ratelimit",
+            enc(user_id)
+        );
+        self.send::<_, ()>(Method::DELETE, url, None, Some(StatusCode::OK))?;
+        Ok(())
+    }
+
+    pub fn list_devices(&mut self, user_id: &str) -> Result<Vec<DeviceInfoV2>, String> {
+        let url = format!("_synapse/admin/v2/users/{}/devices", enc(user_id));
+        let data: ListDevicesV2 =
+            self.send_json::<_, (), _>(Method::GET, url, None, Some(StatusCode::OK))?;
+        Ok(data.devices)
+    }
+
+    pub fn delete_devices(&mut self, user_id: &str, devices: &[String]) -> Result<(), String> {
+        let url = format!("_synapse/admin/v2/users/{}/delete_devices", enc(user_id));
+        self.send(
+            Method::POST,
+            url,
+            Some(DeleteDevicesV2 { devices }),
+            Some(StatusCode::OK),
+        )?;
+        Ok(())
+    }
+
+    pub fn rename_device(
+        &mut self,
+        user_id: &str,
+        device_id: &str,
+        display_name: &str,
+    ) -> Result<(), String> {
+        let url = format!(
+            "_synapse/admin/v2/users/{}/devices/{}",
+            enc(user_id),
+            enc(device_id)
+        );
+        self.send(
+            Method::PUT,
+            url,
+            Some(DeviceNameV2 { display_name }),
+            Some(StatusCode::OK),
+        )?;
+        Ok(())
+    }
+
+    pub fn delete_device(&mut self, user_id: &str, device_id: &str) -> Result<(), String> {
+        let url = format!(
+            "_synapse/admin/v2/users/{}/devices/{}",
+            enc(user_id),
+            enc(device_id)
+        );
+        self.send::<_, ()>(Method::DELETE, url, None, Some(StatusCode::OK))?;
+        Ok(())
+    }
+
+    /// Returns the sessions and connections of a user
+    pub fn whois(&mut self, user_id: &str) -> Result<WhoisV1, String> {
+        let url = format!("_synapse/admin/v1/whois/{}", enc(user_id));
+        self.send_json::<_, (), _>(Method::GET, url, None, Some(StatusCode::OK))
+    }
+
+    /// Logs a user out of all its devices
+    pub fn logout_user(&mut self, user_id: &str) -> Result<usize, String> {
+        let devices: Vec<String> = self
+            .list_devices(user_id)?
+            .drain(..)
+            .map(|d| d.device_id)
+            .collect();
+        if !devices.is_empty() {
+            self.delete_devices(user_id, &devices)?;
+        }
+        Ok(devices.len())
+    }
+
+    /// Returns whether `localpart` can be registered. Taken names return false
+    pub fn username_available(&mut self, localpart: &str) -> Result<bool, String> {
         let url = format!(
-            "_synapse/admin/v2/users?from={}&limit={}&guests=false",
-            offset, page_size
+            "_synapse/admin/v1/username_available?username={}",
+            enc_query(localpart)
         );
-        let r = self.send::<_, ResetPasswordV1>(Method::GET, url, None, Some(StatusCode::OK))?;
+        let r = self.send::<_, ()>(Method::GET, url, None, None)?;
+        let status = r.status();
+        if status == StatusCode::OK {
+            return Ok(true);
+        }
+        let err: MatrixErrorV1 = r.json_or_default();
+        if err.errcode == "M_USER_IN_USE" {
+            return Ok(false);
+        }
+        Err(format!("{} : {}", status, err))
+    }
+
+    /// Creates a new user. Fails if the user already exists
+    pub fn create_user(&mut self, user_id: &str, user: &UserCreateV2) -> Result<(), String> {
+        let url = format!("_synapse/admin/v2/users/{}", enc(user_id));
+        self.send(Method::PUT, url, Some(user), Some(StatusCode::CREATED))?;
+        Ok(())
+    }
+
+    /// Sends a request and deserializes the json response
+    fn send_json<P: Into<Cow<'static, str>>, S: Serialize, T: DeserializeOwned>(
+        &mut self,
+        method: Method,
+        path: P,
+        body: Option<S>,
+        expected_status: Option<StatusCode>,
+    ) -> Result<T, String> {
+        let r = self.send(method, path, body, expected_status)?;
 
         let resp = match r.text() {
             Ok(v) => v,
             Err(_e) => return Err("Server response is invalid".into()),
         };
 
-        let data: ListUserV1 = match serde_json::from_str(&resp) {
-            Ok(v) => v,
-            Err(e) => return Err(format!("Server response is invalid\n{}\n{}", resp, e).into()),
-        };
-
-        Ok(data.users)
+        match serde_json::from_str(&resp) {
+            Ok(v) => Ok(v),
+            Err(e) => Err(format!("Server response is invalid\n{}\n{}", resp, e)),
+        }
     }
 
-    fn send<'b, P: Into<Cow<'static, str>>, S: Serialize>(
-        &'b mut self,
+    fn send<P: Into<Cow<'static, str>>, S: Serialize>(
+        &mut self,
         method: Method,
         path: P,
         body: Option<S>,
@@ -198,10 +453,21 @@ impl Synapse {
         }
 
         if let Some(s) = expected_status {
-            if resp.status() != s {
-                return Err(
-                    format!("{} {} returned {}", method, self.url_cache, resp.status()).into(),
-                );
+            let status = resp.status();
+            if status != s {
+                let err: MatrixErrorV1 = resp.json_or_default();
+                return Err(format!(
+                    "{} {} returned {}{}",
+                    method,
+                    self.url_cache,
+                    status,
+                    if err.error.is_empty() {
+                        String::new()
+                    } else {
+                        format!(" : {}", err)
+                    }
+                )
+                .into());
             }
         }
 
@@ -209,6 +475,53 @@ impl Synapse {
     }
 }
 
+trait ResponseExt {
+    /// Parses the body as json, falling back to the default value
+    fn json_or_default<T: DeserializeOwned + Default>(self) -> T;
+}
+impl ResponseExt for Response {
+    fn json_or_default<T: DeserializeOwned + Default>(self) -> T {
+        self.text()
+            .ok()
+            .and_then(|t| serde_json::from_str(&t).ok())
+            .unwrap_or_default()
+    }
+}
+
+/// Some endpoints expect a json body even though they have no parameters
+#[derive(Serialize)]
+struct EmptyV1 {}
+
+/// Error body returned by the matrix apis
+#[derive(Default, Deserialize)]
+struct MatrixErrorV1 {
+    #[serde(default)]
+    errcode: String,
+    #[serde(default)]
+    error: String,
+}
+impl std::fmt::Display for MatrixErrorV1 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.error, self.errcode)
+    }
+}
+
+#[derive(Default, Deserialize)]
+struct WhoamiV3 {
+    user_id: String,
+}
+
+#[derive(Default, Deserialize)]
+struct LoginFlowV3 {
+    r#type: String,
+}
+
+#[derive(Default, Deserialize)]
+struct LoginFlowsV3 {
+    flows: Vec<LoginFlowV3>,
+}
+
+#[allow(dead_code)]
 #[derive(Default, Serialize)]
 struct AccountValidityV1 {
     user_id: Cow<'static, str>,
@@ -225,6 +538,7 @@ struct ResetPasswordV1 {
     logout_devices: Option<bool>,
 }
 
+#[allow(dead_code)]
 #[derive(Default, Deserialize)]
 struct ListUserV1 {
     pub users: Vec<UserInfoV1>,
@@ -248,6 +562,107 @@ pub struct UserInfoV1 {
     pub creation_ts: usize,
 }
 
+/// Server side filters of the user list
+#[derive(Default, Clone)]
+pub struct UserFilter {
+    /// Only users whose id or display name contains this
+    pub name: String,
+    pub guests: bool,
+    pub deactivated: bool,
+    /// Only shadow-banned users, deactivated ones included. Synapse has no such
+    /// filter: this is a workaround sorting shadow-banned users first, the caller
+    /// has to stop listing at the first user that is not shadow-banned
+    pub shadow_banned: bool,
+}
+
+#[derive(Default, Serialize)]
+pub struct UserModifyV2 {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub displayname: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub admin: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deactivated: Option<bool>,
+}
+
+#[derive(Serialize)]
+struct DeactivateV1 {
+    erase: bool,
+}
+
+#[derive(Serialize)]
+struct UserAdminV1 {
+    admin: bool,
+}
+
+#[allow(dead_code)]
+#[derive(Default, Deserialize)]
+struct ListDevicesV2 {
+    devices: Vec<DeviceInfoV2>,
+    total: usize,
+}
+
+#[derive(Default, Deserialize)]
+pub struct DeviceInfoV2 {
+    pub device_id: String,
+    pub display_name: Option<String>,
+    pub last_seen_ip: Option<String>,
+    pub last_seen_ts: Option<u64>,
+    pub last_seen_user_agent: Option<String>,
+}
+
+#[derive(Default, Deserialize)]
+pub struct WhoisV1 {
+    pub user_id: String,
+    pub devices: HashMap<String, WhoisDeviceV1>,
+}
+
+#[derive(Default, Deserialize)]
+pub struct WhoisDeviceV1 {
+    pub sessions: Vec<WhoisSessionV1>,
+}
+
+#[derive(Default, Deserialize)]
+pub struct WhoisSessionV1 {
+    pub connections: Vec<ConnectionInfoV1>,
+}
+
+#[derive(Default, Deserialize)]
+pub struct ConnectionInfoV1 {
+    pub ip: String,
+    pub last_seen: u64,
+    pub user_agent: String,
+}
+
+#[derive(Serialize)]
+struct DeviceNameV2<'a> {
+    display_name: &'a str,
+}
+
+#[derive(Serialize)]
+struct DeleteDevicesV2<'a> {
+    devices: &'a [String],
+}
+
+#[derive(Default, Serialize)]
+pub struct ThreepidV2 {
+    pub medium: String,
+    pub address: String,
+}
+
+#[derive(Default, Serialize)]
+pub struct UserCreateV2 {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub displayname: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub threepids: Vec<ThreepidV2>,
+    pub admin: bool,
+}
+
 fn bool_from_num<'de, D>(deserializer: D) -> Result<bool, D::Error>
 where
     D: Deserializer<'de>,
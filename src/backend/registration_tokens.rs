@@ -0,0 +1,85 @@
+use reqwest::{Method, StatusCode};
+use serde::{Deserialize, Serialize};
+
+use super::{enc, Synapse};
+
+impl Synapse {
+    /// Lists the registration tokens, only the valid (or invalid) ones if `valid` is set
+    pub fn list_registration_tokens(
+        &mut self,
+        valid: Option<bool>,
+    ) -> Result<Vec<RegistrationTokenV1>, String> {
+        let mut url = String::from("_synapse/admin/v1/registration_tokens");
+        if let Some(v) = valid {
+            url.push_str(&format!("?valid={}", v));
+        }
+        let data: ListRegistrationTokensV1 =
+            self.send_json::<_, (), _>(Method::GET, url, None, Some(StatusCode::OK))?;
+        Ok(data.registration_tokens)
+    }
+
+    /// Creates a token, generated by the server if `token` is None
+    pub fn create_registration_token(
+        &mut self,
+        new: &NewRegistrationTokenV1,
+    ) -> Result<RegistrationTokenV1, String> {
+        self.send_json(
+            Method::POST,
+            "_synapse/admin/v1/registration_tokens/new",
+            Some(new),
+            Some(StatusCode::OK),
+        )
+    }
+
+    pub fn update_registration_token(
+        &mut self,
+        token: &str,
+        changes: &RegistrationTokenUpdateV1,
+    ) -> Result<(), String> {
+        let url = format!("_synapse/admin/v1/registration_tokens/{}", enc(token));
+        self.send(Method::PUT, url, Some(changes), Some(StatusCode::OK))?;
+        Ok(())
+    }
+
+    pub fn delete_registration_token(&mut self, token: &str) -> Result<(), String> {
+        let url = format!("_synapse/admin/v1/registration_tokens/{}", enc(token));
+        self.send::<_, ()>(Method::DELETE, url, None, Some(StatusCode::OK))?;
+        Ok(())
+    }
+}
+
+#[derive(Default, Deserialize)]
+struct ListRegistrationTokensV1 {
+    registration_tokens: Vec<RegistrationTokenV1>,
+}
+
+#[derive(Default, Deserialize)]
+pub struct RegistrationTokenV1 {
+    pub token: String,
+    /// None for unlimited uses
+    pub uses_allowed: Option<u64>,
+    pub pending: u64,
+    pub completed: u64,
+    /// None if the token never expires
+    pub expiry_time: Option<u64>,
+}
+
+#[derive(Default, Serialize)]
+pub struct NewRegistrationTokenV1 {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// Length of the generated token
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub length: Option<u64>,
+    pub uses_allowed: Option<u64>,
+    pub expiry_time: Option<u64>,
+}
+
+/// Only the fields set to Some are modified, Some(None) removes the limit
+#[derive(Default, Serialize)]
+pub struct RegistrationTokenUpdateV1 {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uses_allowed: Option<Option<u64>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expiry_time: Option<Option<u64>>,
+}
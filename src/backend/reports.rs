@@ -0,0 +1,59 @@
+use reqwest::{Method, StatusCode};
+use serde::Deserialize;
+
+use super::Synapse;
+
+impl Synapse {
+    /// Lists the event reports, most recent first
+    pub fn list_event_reports(
+        &mut self,
+        offset: usize,
+        page_size: usize,
+    ) -> Result<ListEventReportsV1, String> {
+        let url = format!(
+            "_synapse/admin/v1/event_reports?from={}&limit={}&dir=b",
+            offset, page_size
+        );
+        self.send_json::<_, (), _>(Method::GET, url, None, Some(StatusCode::OK))
+    }
+
+    /// Returns a report along with the reported event
+    pub fn event_report(&mut self, report_id: u64) -> Result<EventReportV1, String> {
+        let url = format!("_synapse/admin/v1/event_reports/{}", report_id);
+        self.send_json::<_, (), _>(Method::GET, url, None, Some(StatusCode::OK))
+    }
+
+    /// Deletes a report, the reported event is left as is
+    pub fn delete_event_report(&mut self, report_id: u64) -> Result<(), String> {
+        let url = format!("_synapse/admin/v1/event_reports/{}", report_id);
+        self.send::<_, ()>(Method::DELETE, url, None, Some(StatusCode::OK))?;
+        Ok(())
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Default, Deserialize)]
+pub struct ListEventReportsV1 {
+    pub event_reports: Vec<EventReportV1>,
+    /// Number of reports on the server
+    pub total: usize,
+    pub next_token: Option<usize>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct EventReportV1 {
+    pub id: u64,
+    pub received_ts: u64,
+    pub room_id: String,
+    /// Name of the room
+    pub name: Option<String>,
+    pub event_id: String,
+    /// The reporter
+    pub user_id: String,
+    pub reason: Option<String>,
+    pub score: Option<i64>,
+    pub sender: String,
+    /// Only returned by the details endpoint
+    pub event_json: serde_json::Value,
+}
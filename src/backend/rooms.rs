@@ -0,0 +1,558 @@
+use rand::{distributions::Alphanumeric, Rng};
+use reqwest::{Method, StatusCode};
+use serde::{Deserialize, Serialize};
+
+use super::{enc, enc_query, MatrixErrorV1, ResponseExt, Synapse};
+
+impl Synapse {
+    pub fn list_rooms(
+        &mut self,
+        offset: usize,
+        page_size: usize,
+        filter: &RoomFilter,
+    ) -> Result<Vec<RoomInfoV1>, String> {
+        let mut url = format!(
+            "_synapse/admin/v1/rooms?from={}&limit={}",
+            offset, page_size
+        );
+        if !filter.search_term.is_empty() {
+            url.push_str("&search_term=");
+            url.push_str(enc_query(filter.search_term.as_str()).as_str());
+        }
+        if filter.public_only {
+            url.push_str("&public_rooms=true");
+        }
+        let data: ListRoomsV1 =
+            self.send_json::<_, (), _>(Method::GET, url, None, Some(StatusCode::OK))?;
+        Ok(data.rooms)
+    }
+
+    pub fn room_details(&mut self, room_id: &str) -> Result<RoomInfoV1, String> {
+        let url = format!("_synapse/admin/v1/rooms/{}", enc(room_id));
+        self.send_json::<_, (), _>(Method::GET, url, None, Some(StatusCode::OK))
+    }
+
+    /// Returns a page of the local room directory, `since` being the
+    /// `next_batch` of the previous page
+    pub fn public_rooms(
+        &mut self,
+        since: Option<&str>,
+        page_size: usize,
+    ) -> Result<PublicRoomsV3, String> {
+        let mut url = format!("_matrix/client/v3/publicRooms?limit={}", page_size);
+        if let Some(since) = since {
+            url.push_str("&since=");
+            url.push_str(enc_query(since).as_str());
+        }
+        self.send_json::<_, (), _>(Method::GET, url, None, Some(StatusCode::OK))
+    }
+
+    /// Publishes a room in the room directory or removes it from there
+    pub fn set_room_published(&mut self, room_id: &str, published: bool) -> Result<(), String> {
+        let url = format!("_matrix/client/v3/directory/list/room/{}", enc(room_id));
+        self.send(
+            Method::PUT,
+            url,
+            Some(VisibilityV3 {
+                visibility: if published { "public" } else { "private" },
+            }),
+            Some(StatusCode::OK),
+        )?;
+        Ok(())
+    }
+
+    pub fn room_blocked(&mut self, room_id: &str) -> Result<bool, String> {
+        let url = format!("_synapse/admin/v1/rooms/{}/block", enc(room_id));
+        let data: RoomBlockV1 =
+            self.send_json::<_, (), _>(Method::GET, url, None, Some(StatusCode::OK))?;
+        Ok(data.block)
+    }
+
+    /// Blocks or unblocks a room, which does not need to be known to the server
+    pub fn set_room_blocked(&mut self, room_id: &str, block: bool) -> Result<(), String> {
+        let url = format!("_synapse/admin/v1/rooms/{}/block", enc(room_id));
+        self.send(
+            Method::PUT,
+            url,
+            Some(RoomBlockV1 { block }),
+            Some(StatusCode::OK),
+        )?;
+        Ok(())
+    }
+
+    /// Starts purging the history of a room, returns the id of the purge
+    pub fn purge_history(
+        &mut self,
+        room_id: &str,
+        purge: &PurgeHistoryV1,
+    ) -> Result<String, String> {
+        let url = format!("_synapse/admin/v1/purge_history/{}", enc(room_id));
+        let data: PurgeStartedV1 =
+            self.send_json(Method::POST, url, Some(purge), Some(StatusCode::OK))?;
+        Ok(data.purge_id)
+    }
+
+    pub fn purge_history_status(&mut self, purge_id: &str) -> Result<PurgeStatusV1, String> {
+        let url = format!("_synapse/admin/v1/purge_history_status/{}", enc(purge_id));
+        self.send_json::<_, (), _>(Method::GET, url, None, Some(StatusCode::OK))
+    }
+
+    /// Returns the ids of the users joined to a room
+    pub fn room_members(&mut self, room_id: &str) -> Result<Vec<String>, String> {
+        let url = format!("_synapse/admin/v1/rooms/{}/members", enc(room_id));
+        let data: RoomMembersV1 =
+            self.send_json::<_, (), _>(Method::GET, url, None, Some(StatusCode::OK))?;
+        Ok(data.members)
+    }
+
+    /// Returns the current state events of a room
+    pub fn room_state(&mut self, room_id: &str) -> Result<Vec<serde_json::Value>, String> {
+        let url = format!("_synapse/admin/v1/rooms/{}/state", enc(room_id));
+        let data: RoomStateV1 =
+            self.send_json::<_, (), _>(Method::GET, url, None, Some(StatusCode::OK))?;
+        Ok(data.state)
+    }
+
+    pub fn forward_extremities(
+        &mut self,
+        room_id: &str,
+    ) -> Result<Vec<ForwardExtremityV1>, String> {
+        let url = format!(
+            "_synapse/admin/v1/rooms/{}/forward_extremities",
+            enc(room_id)
+        );
+        let data: ForwardExtremitiesV1 =
+            self.send_json::<_, (), _>(Method::GET, url, None, Some(StatusCode::OK))?;
+        Ok(data.results)
+    }
+
+    /// Deletes the forward extremities of a room except the latest ones,
+    /// returns how many were deleted
+    pub fn delete_forward_extremities(&mut self, room_id: &str) -> Result<usize, String> {
+        let url = format!(
+            "_synapse/admin/v1/rooms/{}/forward_extremities",
+            enc(room_id)
+        );
+        let data: DeletedExtremitiesV1 =
+            self.send_json::<_, (), _>(Method::DELETE, url, None, Some(StatusCode::OK))?;
+        Ok(data.deleted)
+    }
+
+    /// Gives the highest power level of the room to a local user, using the
+    /// power of a local room admin
+    pub fn make_room_admin(&mut self, room_id: &str, user_id: &str) -> Result<(), String> {
+        let url = format!("_synapse/admin/v1/rooms/{}/make_room_admin", enc(room_id));
+        self.send(
+            Method::POST,
+            url,
+            Some(UserIdV1 { user_id }),
+            Some(StatusCode::OK),
+        )?;
+        Ok(())
+    }
+
+    /// Joins a local user to a room they can join or are invited to, returns the room id
+    pub fn force_join(&mut self, room_id_or_alias: &str, user_id: &str) -> Result<String, String> {
+        let url = format!("_synapse/admin/v1/join/{}", enc(room_id_or_alias));
+        let data: JoinedV1 = self.send_json(
+            Method::POST,
+            url,
+            Some(UserIdV1 { user_id }),
+            Some(StatusCode::OK),
+        )?;
+        Ok(data.room_id)
+    }
+
+    /// Returns the ids of the rooms a user is joined to
+    pub fn user_joined_rooms(&mut self, user_id: &str) -> Result<Vec<String>, String> {
+        let url = format!("_synapse/admin/v1/users/{}/joined_rooms", enc(user_id));
+        let data: JoinedRoomsV1 =
+            self.send_json::<_, (), _>(Method::GET, url, None, Some(StatusCode::OK))?;
+        Ok(data.joined_rooms)
+    }
+
+    /// Kicks a user out of a room. There is no admin api for it, the admin
+    /// account must be in the room with enough power
+    pub fn kick_user(&mut self, room_id: &str, user_id: &str, reason: &str) -> Result<(), String> {
+        let url = format!("_matrix/client/v3/rooms/{}/kick", enc(room_id));
+        self.send(
+            Method::POST,
+            url,
+            Some(KickV3 {
+                user_id,
+                reason: if reason.is_empty() {
+                    None
+                } else {
+                    Some(reason)
+                },
+            }),
+            Some(StatusCode::OK),
+        )?;
+        Ok(())
+    }
+
+    /// Redacts an event through the client api, the admin account must be in the room
+    pub fn redact_event(
+        &mut self,
+        room_id: &str,
+        event_id: &str,
+        reason: &str,
+    ) -> Result<(), String> {
+        let txn_id: String = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(16)
+            .map(char::from)
+            .collect();
+        let url = format!(
+            "_matrix/client/v3/rooms/{}/redact/{}/{}",
+            enc(room_id),
+            enc(event_id),
+            txn_id
+        );
+        self.send(
+            Method::PUT,
+            url,
+            Some(RedactV3 {
+                reason: if reason.is_empty() {
+                    None
+                } else {
+                    Some(reason)
+                },
+            }),
+            Some(StatusCode::OK),
+        )?;
+        Ok(())
+    }
+
+    /// Returns the local aliases of a room
+    pub fn room_aliases(&mut self, room_id: &str) -> Result<Vec<String>, String> {
+        let url = format!("_matrix/client/v3/rooms/{}/aliases", enc(room_id));
+        let data: RoomAliasesV3 =
+            self.send_json::<_, (), _>(Method::GET, url, None, Some(StatusCode::OK))?;
+        Ok(data.aliases)
+    }
+
+    /// Looks up the room an alias points to, None if the alias does not exist
+    pub fn resolve_room_alias(&mut self, alias: &str) -> Result<Option<RoomAliasV3>, String> {
+        let url = format!("_matrix/client/v3/directory/room/{}", enc(alias));
+        let r = self.send::<_, ()>(Method::GET, url, None, None)?;
+        let status = r.status();
+        if status == StatusCode::OK {
+            return Ok(Some(r.json_or_default()));
+        }
+        let err: MatrixErrorV1 = r.json_or_default();
+        if status == StatusCode::NOT_FOUND || err.errcode == "M_NOT_FOUND" {
+            return Ok(None);
+        }
+        Err(format!("{} : {}", status, err))
+    }
+
+    /// Points a new local alias to a room
+    pub fn create_room_alias(&mut self, alias: &str, room_id: &str) -> Result<(), String> {
+        let url = format!("_matrix/client/v3/directory/room/{}", enc(alias));
+        self.send(
+            Method::PUT,
+            url,
+            Some(NewRoomAliasV3 { room_id }),
+            Some(StatusCode::OK),
+        )?;
+        Ok(())
+    }
+
+    pub fn delete_room_alias(&mut self, alias: &str) -> Result<(), String> {
+        let url = format!("_matrix/client/v3/directory/room/{}", enc(alias));
+        self.send::<_, ()>(Method::DELETE, url, None, Some(StatusCode::OK))?;
+        Ok(())
+    }
+
+    /// Returns a page of the timeline of a room, going back in time from `from` if
+    /// `backwards`, forward otherwise. Without `from` the page starts at the latest event
+    pub fn room_messages(
+        &mut self,
+        room_id: &str,
+        from: Option<&str>,
+        backwards: bool,
+        limit: usize,
+        filter: Option<&serde_json::Value>,
+    ) -> Result<RoomMessagesV1, String> {
+        let mut url = format!(
+            "_synapse/admin/v1/rooms/{}/messages?dir={}&limit={}",
+            enc(room_id),
+            if backwards { "b" } else { "f" },
+            limit
+        );
+        if let Some(from) = from {
+            url.push_str("&from=");
+            url.push_str(enc_query(from).as_str());
+        }
+        if let Some(filter) = filter {
+            url.push_str("&filter=");
+            url.push_str(enc_query(filter.to_string().as_str()).as_str());
+        }
+        self.send_json::<_, (), _>(Method::GET, url, None, Some(StatusCode::OK))
+    }
+
+    /// Returns an event with the events around it and the room state at that point
+    pub fn event_context(
+        &mut self,
+        room_id: &str,
+        event_id: &str,
+        limit: usize,
+    ) -> Result<EventContextV1, String> {
+        let url = format!(
+            "_synapse/admin/v1/rooms/{}/context/{}?limit={}",
+            enc(room_id),
+            enc(event_id),
+            limit
+        );
+        self.send_json::<_, (), _>(Method::GET, url, None, Some(StatusCode::OK))
+    }
+
+    /// Starts redacting the events a user sent in the given rooms, every room they
+    /// are joined to if empty. Returns the id to fetch the progress with
+    pub fn redact_user_events(
+        &mut self,
+        user_id: &str,
+        rooms: &[String],
+        reason: &str,
+    ) -> Result<String, String> {
+        let url = format!("_synapse/admin/v1/user/{}/redact", enc(user_id));
+        let data: RedactIdV1 = self.send_json(
+            Method::POST,
+            url,
+            Some(RedactUserV1 {
+                rooms,
+                reason: if reason.is_empty() {
+                    None
+                } else {
+                    Some(reason)
+                },
+            }),
+            Some(StatusCode::OK),
+        )?;
+        Ok(data.redact_id)
+    }
+
+    pub fn redact_user_events_status(&mut self, redact_id: &str) -> Result<RedactStatusV1, String> {
+        let url = format!("_synapse/admin/v1/user/redact_status/{}", enc(redact_id));
+        self.send_json::<_, (), _>(Method::GET, url, None, Some(StatusCode::OK))
+    }
+}
+
+/// Server side filters of the room list
+#[derive(Default, Clone)]
+pub struct RoomFilter {
+    /// Only rooms whose name, alias or id contains this
+    pub search_term: String,
+    /// Only rooms published in the room directory
+    pub public_only: bool,
+}
+
+#[allow(dead_code)]
+#[derive(Default, Deserialize)]
+struct ListRoomsV1 {
+    rooms: Vec<RoomInfoV1>,
+    total_rooms: usize,
+    next_batch: Option<usize>,
+}
+
+/// A room as listed by the admin api. The details endpoint fills in a few more fields
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct RoomInfoV1 {
+    pub room_id: String,
+    pub name: Option<String>,
+    pub topic: Option<String>,
+    pub canonical_alias: Option<String>,
+    pub joined_members: u64,
+    pub joined_local_members: u64,
+    pub version: Option<String>,
+    pub creator: Option<String>,
+    pub encryption: Option<String>,
+    pub federatable: bool,
+    pub public: bool,
+    pub join_rules: Option<String>,
+    pub guest_access: Option<String>,
+    pub history_visibility: Option<String>,
+    pub state_events: u64,
+    pub room_type: Option<String>,
+}
+
+#[allow(dead_code)]
+#[derive(Default, Deserialize)]
+struct JoinedRoomsV1 {
+    joined_rooms: Vec<String>,
+    total: usize,
+}
+
+#[allow(dead_code)]
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct PublicRoomsV3 {
+    pub chunk: Vec<PublicRoomV3>,
+    pub next_batch: Option<String>,
+    pub total_room_count_estimate: Option<u64>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct PublicRoomV3 {
+    pub room_id: String,
+    pub name: Option<String>,
+    pub canonical_alias: Option<String>,
+    pub num_joined_members: u64,
+}
+
+#[derive(Serialize)]
+struct VisibilityV3 {
+    visibility: &'static str,
+}
+
+/// Events before either the event or the timestamp are purged
+#[derive(Default, Serialize)]
+pub struct PurgeHistoryV1 {
+    /// Also delete the events sent by local users, which cannot be fetched back
+    pub delete_local_events: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub purge_up_to_event_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub purge_up_to_ts: Option<u64>,
+}
+
+#[derive(Default, Deserialize)]
+struct PurgeStartedV1 {
+    purge_id: String,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct PurgeStatusV1 {
+    /// active, complete or failed
+    pub status: String,
+    pub error: Option<String>,
+}
+
+#[derive(Default, Deserialize, Serialize)]
+struct RoomBlockV1 {
+    block: bool,
+}
+
+#[allow(dead_code)]
+#[derive(Default, Deserialize)]
+struct RoomMembersV1 {
+    members: Vec<String>,
+    total: usize,
+}
+
+#[derive(Default, Deserialize)]
+struct RoomStateV1 {
+    state: Vec<serde_json::Value>,
+}
+
+#[allow(dead_code)]
+#[derive(Default, Deserialize)]
+struct ForwardExtremitiesV1 {
+    count: usize,
+    results: Vec<ForwardExtremityV1>,
+}
+
+/// Latest event of a branch of the room DAG
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct ForwardExtremityV1 {
+    pub event_id: String,
+    pub state_group: Option<u64>,
+    pub depth: u64,
+    pub received_ts: u64,
+}
+
+#[derive(Default, Deserialize)]
+struct DeletedExtremitiesV1 {
+    deleted: usize,
+}
+
+#[derive(Serialize)]
+struct UserIdV1<'a> {
+    user_id: &'a str,
+}
+
+#[derive(Default, Deserialize)]
+struct JoinedV1 {
+    room_id: String,
+}
+
+#[derive(Serialize)]
+struct KickV3<'a> {
+    user_id: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct RedactV3<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<&'a str>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct RoomAliasesV3 {
+    aliases: Vec<String>,
+}
+
+/// Where an alias points to
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct RoomAliasV3 {
+    pub room_id: String,
+    pub servers: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct NewRoomAliasV3<'a> {
+    room_id: &'a str,
+}
+
+/// A page of the timeline, `end` continuing in the same direction
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct RoomMessagesV1 {
+    pub chunk: Vec<serde_json::Value>,
+    pub start: String,
+    pub end: Option<String>,
+}
+
+/// An event and its surroundings, `events_before` being the most recent first
+#[allow(dead_code)]
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct EventContextV1 {
+    pub events_before: Vec<serde_json::Value>,
+    pub event: serde_json::Value,
+    pub events_after: Vec<serde_json::Value>,
+    pub state: Vec<serde_json::Value>,
+    pub start: String,
+    pub end: String,
+}
+
+#[derive(Serialize)]
+struct RedactUserV1<'a> {
+    rooms: &'a [String],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<&'a str>,
+}
+
+#[derive(Default, Deserialize)]
+struct RedactIdV1 {
+    redact_id: String,
+}
+
+/// Progress of the redaction of a user's events, `status` being one of
+/// scheduled, active, completed or failed
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct RedactStatusV1 {
+    pub status: String,
+    /// Error of each event that could not be redacted
+    pub failed_redactions: std::collections::HashMap<String, String>,
+}
@@ -0,0 +1,58 @@
+use reqwest::{Method, StatusCode};
+use serde::{Deserialize, Serialize};
+
+use super::Synapse;
+
+impl Synapse {
+    /// Sends a message to `user_id` from the server notices account, returns the event id
+    pub fn send_server_notice(
+        &mut self,
+        user_id: &str,
+        notice: &NoticeContentV1,
+    ) -> Result<String, String> {
+        let r: ServerNoticeSentV1 = self.send_json(
+            Method::POST,
+            "_synapse/admin/v1/send_server_notice",
+            Some(ServerNoticeV1 {
+                user_id,
+                content: notice,
+            }),
+            Some(StatusCode::OK),
+        )?;
+        Ok(r.event_id)
+    }
+}
+
+#[derive(Serialize)]
+struct ServerNoticeV1<'a> {
+    user_id: &'a str,
+    content: &'a NoticeContentV1,
+}
+
+#[derive(Deserialize)]
+struct ServerNoticeSentV1 {
+    event_id: String,
+}
+
+/// Content of a `m.room.message` notice
+#[derive(Clone, Serialize)]
+pub struct NoticeContentV1 {
+    pub msgtype: &'static str,
+    pub body: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub formatted_body: Option<String>,
+}
+
+impl NoticeContentV1 {
+    /// Plain text notice, with an optional HTML version
+    pub fn new(body: &str, html: Option<&str>) -> Self {
+        Self {
+            msgtype: "m.text",
+            body: body.to_string(),
+            format: html.map(|_| "org.matrix.custom.html"),
+            formatted_body: html.map(|h| h.to_string()),
+        }
+    }
+}
@@ -0,0 +1,53 @@
+/// A list of items processed one at a time (usually one per tick) so progress
+/// can be drawn in between requests
+pub struct Batch<T> {
+    pub items: Vec<T>,
+    pub next: usize,
+    /// Index of the failed items along with the reason
+    pub failures: Vec<(usize, String)>,
+}
+
+impl<T> Batch<T> {
+    pub fn new(items: Vec<T>) -> Self {
+        Self {
+            items,
+            next: 0,
+            failures: Vec::new(),
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.next >= self.items.len()
+    }
+
+    pub fn succeeded(&self) -> usize {
+        self.next - self.failures.len()
+    }
+
+    /// Runs `f` on the next item. Returns false if there was nothing left to process
+    pub fn step<F>(&mut self, f: F) -> bool
+    where
+        F: FnOnce(&mut T) -> Result<(), String>,
+    {
+        let idx = self.next;
+        let item = match self.items.get_mut(idx) {
+            Some(i) => i,
+            None => return false,
+        };
+        if let Err(e) = f(item) {
+            self.failures.push((idx, e));
+        }
+        self.next += 1;
+        true
+    }
+
+    /// Single line summary of the progress
+    pub fn progress(&self) -> String {
+        format!(
+            "{}/{} processed, {} failed",
+            self.next,
+            self.items.len(),
+            self.failures.len()
+        )
+    }
+}
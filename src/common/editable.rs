@@ -1,12 +1,15 @@
 use std::ops::Deref;
 
-use crossterm::event::{KeyCode, KeyEvent};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use tui::{
     style::{Color, Modifier, Style},
-    text::Span,
+    text::{Span, Spans},
 };
 
-use super::*;
+use super::{
+    human::{format_ts, now_ms, parse_time},
+    *,
+};
 
 pub trait EditableWidget {
     /// Returns the contents of the widget as a string
@@ -36,6 +39,147 @@ impl MutStr {
         };
         &mut self.cur
     }
+
+    /// Offset of the start of the line containing `offset`
+    fn line_start(&self, offset: usize) -> usize {
+        self.cur[..offset].rfind('\n').map(|i| i + 1).unwrap_or(0)
+    }
+
+    /// Offset of the end of the line containing `offset`
+    fn line_end(&self, offset: usize) -> usize {
+        self.cur[offset..]
+            .find('\n')
+            .map(|i| offset + i)
+            .unwrap_or(self.cur.len())
+    }
+
+    /// Index of the line the cursor is on
+    fn cursor_line(&self) -> usize {
+        self.cur[..self.cursor].matches('\n').count()
+    }
+
+    /// Moves the cursor to the same column on the line starting at `line_start`
+    fn move_to_line(&mut self, line_start: usize) {
+        let col = self.cur[self.line_start(self.cursor)..self.cursor]
+            .chars()
+            .count();
+        let line_end = self.line_end(line_start);
+        self.cursor = self.cur[line_start..line_end]
+            .char_indices()
+            .nth(col)
+            .map(|(i, _)| line_start + i)
+            .unwrap_or(line_end);
+    }
+
+    /// Offset of the start of the word before the cursor
+    fn prev_word(&self) -> usize {
+        let before = &self.cur[..self.cursor];
+        let end = before.trim_end().len();
+        before[..end]
+            .rfind(char::is_whitespace)
+            .map(|i| i + 1)
+            .unwrap_or(0)
+    }
+
+    /// Offset of the end of the word after the cursor
+    fn next_word(&self) -> usize {
+        let after = &self.cur[self.cursor..];
+        let start = after.len() - after.trim_start().len();
+        after[start..]
+            .find(char::is_whitespace)
+            .map(|i| self.cursor + start + i)
+            .unwrap_or(self.cur.len())
+    }
+
+    /// Handles the keys moving between lines of a multi-line text, returns
+    /// None for the keys shared with single line strings
+    fn handle_multiline(&mut self, key: &KeyEvent) -> Option<HandleRes> {
+        Some(match key.code {
+            // Ctrl+Enter finishes the edit
+            KeyCode::Enter if key.modifiers.contains(KeyModifiers::CONTROL) => HandleRes::Ignored,
+            KeyCode::Enter => {
+                let cursor = self.cursor;
+                self.save_cur().insert(cursor, '\n');
+                self.cursor += 1;
+                HandleRes::ReDraw
+            }
+            KeyCode::Up => {
+                let start = self.line_start(self.cursor);
+                if start == 0 {
+                    // Let the container move to the previous field
+                    return Some(HandleRes::Ignored);
+                }
+                self.move_to_line(self.line_start(start - 1));
+                HandleRes::ReDraw
+            }
+            KeyCode::Down => {
+                let end = self.line_end(self.cursor);
+                if end == self.cur.len() {
+                    return Some(HandleRes::Ignored);
+                }
+                self.move_to_line(end + 1);
+                HandleRes::ReDraw
+            }
+            KeyCode::Home => {
+                self.cursor = self.line_start(self.cursor);
+                HandleRes::ReDraw
+            }
+            KeyCode::End => {
+                self.cursor = self.line_end(self.cursor);
+                HandleRes::ReDraw
+            }
+            _ => return None,
+        })
+    }
+
+    /// Scrolls the visible lines so the cursor stays in view
+    fn scroll_to_cursor(&self, scroll: &mut usize) {
+        let line = self.cursor_line();
+        if line < *scroll {
+            *scroll = line;
+        } else if line >= *scroll + TEXT_ROWS {
+            *scroll = line + 1 - TEXT_ROWS;
+        }
+    }
+}
+
+/// Number of lines shown for multi-line texts
+const TEXT_ROWS: usize = 6;
+
+/// Maps the keys some terminals send for Ctrl+Enter to it
+pub fn normalize_key(key: &KeyEvent) -> KeyEvent {
+    match key.code {
+        KeyCode::Char('j') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            KeyEvent::new(KeyCode::Enter, KeyModifiers::CONTROL)
+        }
+        _ => *key,
+    }
+}
+
+/// Spans of `line` with the char at `cursor` underlined, if any
+fn cursor_spans(line: &str, cursor: Option<usize>) -> Vec<Span<'_>> {
+    let cursor = match cursor {
+        Some(c) => c,
+        None => return vec![Span::raw(line)],
+    };
+    let underlined = Style::default().add_modifier(Modifier::UNDERLINED);
+    let mut r = Vec::with_capacity(3);
+    if cursor > 0 {
+        r.push(Span::raw(&line[..cursor]));
+    }
+    if cursor < line.len() {
+        let cursor_char = decode_char(line.as_bytes(), cursor);
+        r.push(Span::styled(cursor_char, underlined));
+        r.push(Span::raw(&line[cursor + cursor_char.len()..]));
+    } else {
+        r.push(Span::styled(
+            " ",
+            Style::default()
+                .fg(Color::DarkGray)
+                .add_modifier(Modifier::UNDERLINED),
+        ));
+    }
+    r
 }
 
 pub enum Editable {
@@ -43,12 +187,18 @@ pub enum Editable {
     ConstBool(bool),
     Str(MutStr),
     Bool(bool, Option<bool>),
+    /// Digits only, empty meaning no value
+    Num(MutStr),
+    /// A date or an age, see [crate::common::human::parse_time]
+    Date(MutStr),
+    /// Multi-line text, along with the first visible line
+    Text(MutStr, usize),
 }
 impl EditableWidget for Editable {
     fn as_str(&self) -> &str {
         match self {
             Self::ConstStr(s) => s,
-            Self::Str(s) => s.cur.deref(),
+            Self::Str(s) | Self::Num(s) | Self::Date(s) | Self::Text(s, _) => s.cur.deref(),
             Self::Bool(b, ..) | Self::ConstBool(b) => {
                 if *b {
                     "true"
@@ -58,38 +208,41 @@ impl EditableWidget for Editable {
             }
         }
     }
-    fn as_spans(&self, is_editing: bool) -> Vec<Span> {
+    fn as_spans(&self, is_editing: bool) -> Vec<Span<'_>> {
+        if let Editable::Text(s, _) = self {
+            // Single line view of the text, the line being edited or the first one
+            let offset = if is_editing { s.cursor } else { 0 };
+            let start = s.line_start(offset);
+            let line = &s.cur[start..s.line_end(offset)];
+            let mut r = cursor_spans(line, is_editing.then_some(s.cursor - start));
+            if s.cur.contains('\n') {
+                r.push(Span::styled(" …", Style::default().fg(Color::DarkGray)));
+            }
+            return r;
+        }
         if !is_editing {
             return vec![Span::raw(self.as_str())];
         }
 
-        let underlined = Style::default().add_modifier(Modifier::UNDERLINED);
-        if let Editable::Str(s) = self {
-            let cur = s.cur.deref();
+        if let Editable::Str(s) | Editable::Num(s) | Editable::Date(s) = self {
             // Return spans with the cursor position underlined
-            let mut r = Vec::with_capacity(3);
-            if s.cursor > 0 {
-                r.push(Span::raw(&cur[..s.cursor]));
-            }
-            if s.cursor < cur.len() {
-                let cursor_char = decode_char(cur.as_bytes(), s.cursor);
-                r.push(Span::styled(cursor_char, underlined));
-                r.push(Span::raw(&cur[s.cursor + cursor_char.len()..]));
-            } else {
-                r.push(Span::styled(
-                    " ",
-                    Style::default()
-                        .fg(Color::DarkGray)
-                        .add_modifier(Modifier::UNDERLINED),
-                ));
-            }
-            r
+            cursor_spans(s.cur.deref(), Some(s.cursor))
         } else {
-            vec![Span::styled(self.as_str(), underlined)]
+            vec![Span::styled(
+                self.as_str(),
+                Style::default().add_modifier(Modifier::UNDERLINED),
+            )]
         }
     }
 
     fn handle_event(&mut self, key: &KeyEvent) -> HandleRes {
+        if let Self::Text(s, scroll) = self {
+            let r = s.handle_multiline(key);
+            s.scroll_to_cursor(scroll);
+            if let Some(r) = r {
+                return r;
+            }
+        }
         let r = match self {
             Self::Bool(cur, orig) => {
                 if let KeyCode::Enter = key.code {
@@ -102,7 +255,18 @@ impl EditableWidget for Editable {
                     HandleRes::Ignored
                 }
             }
-            Self::Str(s) => match key.code {
+            Self::Num(_) if matches!(key.code, KeyCode::Char(c) if !c.is_ascii_digit()) => {
+                HandleRes::Handled
+            }
+            Self::Str(s) | Self::Num(s) | Self::Date(s) | Self::Text(s, _) => match key.code {
+                KeyCode::Left if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    s.cursor = s.prev_word();
+                    HandleRes::ReDraw
+                }
+                KeyCode::Right if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    s.cursor = s.next_word();
+                    HandleRes::ReDraw
+                }
                 KeyCode::Char(c) => {
                     let cursor = s.cursor;
                     let mut_cur = s.save_cur();
@@ -138,23 +302,20 @@ impl EditableWidget for Editable {
                         HandleRes::Handled
                     }
                 }
-                KeyCode::Left => {
-                    if s.cursor > 0 {
-                        dec_val(&mut s.cursor, 1);
+                KeyCode::Left => match s.cur[..s.cursor].chars().next_back() {
+                    Some(c) => {
+                        s.cursor -= c.len_utf8();
                         HandleRes::ReDraw
-                    } else {
-                        HandleRes::Handled
                     }
-                }
-                KeyCode::Right => {
-                    let old = s.cursor;
-                    inc_val(&mut s.cursor, 1, s.cur.deref().len() + 1);
-                    if old != s.cursor {
+                    None => HandleRes::Handled,
+                },
+                KeyCode::Right => match s.cur[s.cursor..].chars().next() {
+                    Some(c) => {
+                        s.cursor += c.len_utf8();
                         HandleRes::ReDraw
-                    } else {
-                        HandleRes::Handled
                     }
-                }
+                    None => HandleRes::Handled,
+                },
                 KeyCode::End => {
                     if s.cursor != s.cur.deref().len() {
                         s.cursor = s.cur.deref().len();
@@ -175,6 +336,9 @@ impl EditableWidget for Editable {
             },
             _ => HandleRes::Ignored,
         };
+        if let Self::Text(s, scroll) = self {
+            s.scroll_to_cursor(scroll);
+        }
         r
     }
 
@@ -188,7 +352,7 @@ impl EditableWidget for Editable {
                     false
                 }
             }
-            Self::Str(s) => {
+            Self::Str(s) | Self::Num(s) | Self::Date(s) | Self::Text(s, _) => {
                 if let Some(orig) = s.orig.as_deref() {
                     s.cur != orig
                 } else {
@@ -204,10 +368,17 @@ impl EditableWidget for Editable {
 
     fn restore_orig(&mut self) {
         match self {
-            Self::Str(s) => {
-                s.cur.clear();
-                if let Some(orig) = s.orig.as_deref() {
-                    s.cur.push_str(orig);
+            Self::Str(s) | Self::Num(s) | Self::Date(s) => {
+                if let Some(orig) = s.orig.take() {
+                    s.cur = orig;
+                    s.cursor = s.cur.len();
+                }
+            }
+            Self::Text(s, scroll) => {
+                if let Some(orig) = s.orig.take() {
+                    s.cur = orig;
+                    s.cursor = s.cur.len();
+                    s.scroll_to_cursor(scroll);
                 }
             }
             Self::Bool(cur, orig) => {
@@ -220,7 +391,7 @@ impl EditableWidget for Editable {
     }
     fn forget_orig(&mut self) {
         match self {
-            Self::Str(s) => {
+            Self::Str(s) | Self::Num(s) | Self::Date(s) | Self::Text(s, _) => {
                 s.orig.take();
             }
             Self::Bool(_cur, orig) => {
@@ -250,8 +421,94 @@ impl Editable {
         })
     }
 
+    /// Constructs an editable multi-line text
+    pub fn text(s: &str) -> Self {
+        let s = MutStr {
+            cur: s.to_string(),
+            orig: None,
+            cursor: s.len(),
+        };
+        let mut scroll = 0;
+        s.scroll_to_cursor(&mut scroll);
+        Self::Text(s, scroll)
+    }
+
     /// Constructs an editable bool
     pub fn bool(b: bool) -> Self {
         Self::Bool(b, None)
     }
+
+    /// Constructs an editable number, None being displayed as empty
+    pub fn num(n: Option<u64>) -> Self {
+        let s = n.map(|n| n.to_string()).unwrap_or_default();
+        Self::Num(MutStr {
+            cursor: s.len(),
+            cur: s,
+            orig: None,
+        })
+    }
+
+    /// Constructs an editable timestamp, None being displayed as empty
+    pub fn date(ts_ms: Option<u64>) -> Self {
+        let s = ts_ms.map(format_ts).unwrap_or_default();
+        Self::Date(MutStr {
+            cursor: s.len(),
+            cur: s,
+            orig: None,
+        })
+    }
+
+    /// Number of terminal rows needed to show the widget
+    pub fn height(&self) -> u16 {
+        match self {
+            Self::Text(..) => TEXT_ROWS as u16,
+            _ => 1,
+        }
+    }
+
+    /// Visible lines of the widget, see [Self::height]
+    pub fn as_lines(&self, is_editing: bool) -> Vec<Spans<'_>> {
+        let (s, scroll) = match self {
+            Self::Text(s, scroll) => (s, *scroll),
+            _ => return vec![Spans::from(self.as_spans(is_editing))],
+        };
+        let mut start = 0;
+        let mut lines = Vec::with_capacity(TEXT_ROWS);
+        for (idx, line) in s.cur.split('\n').enumerate() {
+            if idx >= scroll + TEXT_ROWS {
+                break;
+            }
+            if idx >= scroll {
+                let end = start + line.len();
+                let cursor = if is_editing && (start..=end).contains(&s.cursor) {
+                    Some(s.cursor - start)
+                } else {
+                    None
+                };
+                lines.push(Spans::from(cursor_spans(line, cursor)));
+            }
+            start += line.len() + 1;
+        }
+        lines
+    }
+
+    /// Value of a number, None if empty
+    pub fn as_num(&self) -> Result<Option<u64>, String> {
+        let s = self.as_str().trim();
+        if s.is_empty() {
+            return Ok(None);
+        }
+        s.parse()
+            .map(Some)
+            .map_err(|_| format!("Invalid number '{}'", s))
+    }
+
+    /// Value of a date relative to now, None if empty
+    pub fn as_ts(&self) -> Result<Option<u64>, String> {
+        let s = self.as_str().trim();
+        if s.is_empty() {
+            return Ok(None);
+        }
+        parse_time(s, now_ms()).map(Some)
+    }
 }
@@ -0,0 +1,254 @@
+/// Formats a timestamp in milliseconds as `YYYY-MM-DD HH:MM` (UTC)
+pub fn format_ts(ts_ms: u64) -> String {
+    let secs = ts_ms / 1000;
+    let (y, m, d) = civil_from_days((secs / 86400) as i64);
+    let rem = secs % 86400;
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        y,
+        m,
+        d,
+        rem / 3600,
+        (rem % 3600) / 60
+    )
+}
+
+/// Formats an optional timestamp, 0 or None being displayed as "-"
+pub fn format_opt_ts(ts_ms: Option<u64>) -> String {
+    match ts_ms {
+        Some(ts) if ts > 0 => format_ts(ts),
+        _ => "-".into(),
+    }
+}
+
+/// Current time in milliseconds since the epoch
+pub fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Parses a point in time given either as a date (`YYYY-MM-DD [HH:MM]`, UTC) or as
+/// an age relative to `now_ms` (`90d`, `2w`, `12h`, `30m`, `1y`, optionally followed by "ago").
+/// Ages prefixed with `+` or "in" are in the future (`+7d`, `in 2w`)
+pub fn parse_time(input: &str, now_ms: u64) -> Result<u64, String> {
+    let s = input.trim();
+    let err = || {
+        format!(
+            "Invalid time '{}', expected YYYY-MM-DD [HH:MM] or an age like 30d",
+            input
+        )
+    };
+    if let Some(age) = parse_age(s.strip_suffix("ago").unwrap_or(s).trim()) {
+        return Ok(now_ms.saturating_sub(age));
+    }
+    // Ages in the future, e.g. for expiration dates
+    if let Some(age) = s
+        .strip_prefix('+')
+        .or_else(|| s.strip_prefix("in "))
+        .and_then(|a| parse_age(a.trim()))
+    {
+        return now_ms.checked_add(age).ok_or_else(err);
+    }
+
+    let (date, time) = match s.split_once([' ', 'T']) {
+        Some((d, t)) => (d, Some(t.trim())),
+        None => (s, None),
+    };
+    let mut parts = date.split('-').map(|p| p.parse::<i64>());
+    let (y, m, d) = match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(Ok(y)), Some(Ok(m)), Some(Ok(d)), None) => (y, m, d),
+        _ => return Err(err()),
+    };
+    if !(1970..=9999).contains(&y) || !(1..=12).contains(&m) {
+        return Err(err());
+    }
+    if d < 1 || d > days_in_month(y, m as u32) as i64 {
+        return Err(err());
+    }
+    let mut secs = days_from_civil(y, m as u32, d as u32) * 86400;
+    if let Some(t) = time {
+        let (h, min) = t.split_once(':').ok_or_else(err)?;
+        let h: i64 = h.parse().map_err(|_| err())?;
+        let min: i64 = min.parse().map_err(|_| err())?;
+        if !(0..24).contains(&h) || !(0..60).contains(&min) {
+            return Err(err());
+        }
+        secs += h * 3600 + min * 60;
+    }
+    Ok(secs as u64 * 1000)
+}
+
+/// Parses an age like `90d` into milliseconds
+fn parse_age(s: &str) -> Option<u64> {
+    let split = s.find(|c: char| !c.is_ascii_digit())?;
+    let (num, unit) = s.split_at(split);
+    let num: u64 = num.parse().ok()?;
+    let secs = match unit.trim() {
+        "s" => 1,
+        "m" | "min" => 60,
+        "h" => 3600,
+        "d" => 86400,
+        "w" => 7 * 86400,
+        "y" => 365 * 86400,
+        _ => return None,
+    };
+    num.checked_mul(secs * 1000)
+}
+
+/// Parses a size like `50MB`, `1.5GiB` or `1024` (bytes). KB/MB/GB are powers of
+/// 1000, KiB/MiB/GiB powers of 1024
+pub fn parse_size(input: &str) -> Result<u64, String> {
+    let s = input.trim();
+    let split = s
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(s.len());
+    let (num, unit) = s.split_at(split);
+    let num: f64 = num
+        .parse()
+        .map_err(|_| format!("Invalid size '{}', expected something like 50MB", input))?;
+    let mult: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" => 1000,
+        "kib" => 1 << 10,
+        "m" | "mb" => 1000 * 1000,
+        "mib" => 1 << 20,
+        "g" | "gb" => 1000 * 1000 * 1000,
+        "gib" => 1 << 30,
+        _ => return Err(format!("Unknown size unit '{}'", unit)),
+    };
+    Ok((num * mult as f64) as u64)
+}
+
+/// Number of days in month `m` of year `y`
+fn days_in_month(y: i64, m: u32) -> u32 {
+    match m {
+        4 | 6 | 9 | 11 => 30,
+        2 if y % 4 == 0 && (y % 100 != 0 || y % 400 == 0) => 29,
+        2 => 28,
+        _ => 31,
+    }
+}
+
+/// Converts a (year, month, day) date into a number of days since the epoch
+fn days_from_civil(y: i64, m: u32, d: u32) -> i64 {
+    // http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y.rem_euclid(400);
+    let mp = if m > 2 { m - 3 } else { m + 9 } as i64;
+    let doy = (153 * mp + 2) / 5 + d as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+/// Converts a number of days since the epoch into a (year, month, day) date
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let y = yoe + era * 400 + if m <= 2 { 1 } else { 0 };
+    (y, m, d)
+}
+
+/// Formats a number of bytes with a binary unit, e.g. `1.5 MiB`
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+/// Formats the time elapsed since `ts_ms`, e.g. `3d ago`
+pub fn format_age(ts_ms: u64, now_ms: u64) -> String {
+    format!("{} ago", format_duration(now_ms.saturating_sub(ts_ms)))
+}
+
+/// Formats a duration in its largest unit, e.g. `12h`
+pub fn format_duration(ms: u64) -> String {
+    let secs = ms / 1000;
+    if secs < 60 {
+        format!("{}s", secs)
+    } else if secs < 3600 {
+        format!("{}m", secs / 60)
+    } else if secs < 86400 {
+        format!("{}h", secs / 3600)
+    } else {
+        format!("{}d", secs / 86400)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: u64 = 86400 * 1000;
+
+    #[test]
+    fn parse_age_units() {
+        assert_eq!(parse_age("30s"), Some(30 * 1000));
+        assert_eq!(parse_age("5min"), Some(5 * 60 * 1000));
+        assert_eq!(parse_age("12h"), Some(12 * 3600 * 1000));
+        assert_eq!(parse_age("2w"), Some(14 * DAY));
+        assert_eq!(parse_age("1y"), Some(365 * DAY));
+        assert_eq!(parse_age("3 d"), Some(3 * DAY));
+        assert_eq!(parse_age("3"), None);
+        assert_eq!(parse_age("d"), None);
+        assert_eq!(parse_age("3x"), None);
+        assert_eq!(parse_age("99999999999y"), None);
+    }
+
+    #[test]
+    fn parse_time_ages() {
+        let now = 1000 * DAY;
+        assert_eq!(parse_time("90d", now), Ok(910 * DAY));
+        assert_eq!(parse_time(" 90d ago ", now), Ok(910 * DAY));
+        assert_eq!(parse_time("2000d", now), Ok(0));
+        assert_eq!(parse_time("+7d", now), Ok(1007 * DAY));
+        assert_eq!(parse_time("in 1w", now), Ok(1007 * DAY));
+        assert!(parse_time("99999999999y", now).is_err());
+        assert!(parse_time("+99999999999y", now).is_err());
+        assert!(parse_time("+5000000y", u64::MAX - 1).is_err());
+    }
+
+    #[test]
+    fn parse_time_dates() {
+        assert_eq!(parse_time("1970-01-01", 0), Ok(0));
+        assert_eq!(parse_time("1970-01-02 01:30", 0), Ok(DAY + 5400 * 1000));
+        assert_eq!(parse_time("2024-02-29T00:00", 0), Ok(19782 * DAY));
+        assert!(parse_time("2024-02-30", 0).is_err());
+        assert!(parse_time("2023-02-29", 0).is_err());
+        assert!(parse_time("2024-04-31", 0).is_err());
+        assert!(parse_time("2024-13-01", 0).is_err());
+        assert!(parse_time("1969-12-31", 0).is_err());
+        assert!(parse_time("99999999999-01-01", 0).is_err());
+        assert!(parse_time("2024-01-01 24:00", 0).is_err());
+        assert!(parse_time("2024-01", 0).is_err());
+        assert!(parse_time("yesterday", 0).is_err());
+    }
+
+    #[test]
+    fn parse_sizes() {
+        assert_eq!(parse_size("1024"), Ok(1024));
+        assert_eq!(parse_size("50MB"), Ok(50_000_000));
+        assert_eq!(parse_size("1.5 GiB"), Ok(3 << 29));
+        assert_eq!(parse_size("2kib"), Ok(2048));
+        assert!(parse_size("MB").is_err());
+        assert!(parse_size("5 XB").is_err());
+    }
+}
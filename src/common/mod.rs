@@ -1,5 +1,5 @@
 use std::{
-    io::Stdout,
+    io::{Stdout, Write},
     ops::{AddAssign, SubAssign},
 };
 
@@ -17,6 +17,11 @@ pub enum HandleRes {
     Ignored,
 }
 
+/// Something another view can be asked to show
+pub enum Goto {
+    Room(String),
+}
+
 pub trait ViewImpl<S> {
     /// The display value of this view
     fn title(&self) -> &'static str;
@@ -42,10 +47,32 @@ pub trait ViewImpl<S> {
     fn leave_view(&mut self, _state: &mut S) -> HandleRes {
         HandleRes::Ignored
     }
+
+    /// Called periodically when no events are pending. Views with background work
+    /// should return `ReDraw` while they are busy
+    fn tick(&mut self, _state: &mut S) -> HandleRes {
+        HandleRes::Ignored
+    }
+
+    /// Asked to show `target` on behalf of another view. Returns true if this view
+    /// handles it, in which case it becomes the current tab
+    fn goto(&mut self, _target: &Goto, _state: &mut S) -> bool {
+        false
+    }
+
+    /// View specific key bindings displayed in the help bar
+    fn hints(&self) -> Vec<(&'static str, &'static str)> {
+        Vec::new()
+    }
 }
 
+pub mod batch;
 pub mod editable;
+pub mod human;
+pub mod picker;
 pub mod prompt;
+pub mod table;
+pub mod tree;
 
 /// Increments `orig` by `amount` without going >= `max`
 pub fn inc_val(orig: &mut usize, amount: usize, max: usize) -> usize {
@@ -60,7 +87,7 @@ pub fn inc_val(orig: &mut usize, amount: usize, max: usize) -> usize {
             return overflow;
         }
     }
-    return 0;
+    0
 }
 
 /// Decrements `orig` by `amount` without going bellow 0
@@ -74,9 +101,9 @@ pub fn dec_val(orig: &mut usize, amount: usize) {
 
 pub fn apply_offset(orig: &mut usize, offset: isize, max: usize) -> usize {
     if offset > 0 {
-        return inc_val(orig, offset.abs() as usize, max);
+        return inc_val(orig, offset.unsigned_abs(), max);
     }
-    dec_val(orig, offset.abs() as usize);
+    dec_val(orig, offset.unsigned_abs());
     0
 }
 
@@ -92,3 +119,10 @@ pub fn decode_char(bytes: &[u8], offset: usize) -> &str {
     }
     cur_char
 }
+
+/// Asks the terminal to put `text` in the clipboard (OSC 52)
+pub fn copy_to_clipboard(text: &str) -> std::io::Result<()> {
+    let mut stdout = std::io::stdout();
+    write!(stdout, "\x1b]52;c;{}\x07", base64::encode(text))?;
+    stdout.flush()
+}
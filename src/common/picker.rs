@@ -0,0 +1,143 @@
+use std::io::Stdout;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use tui::{
+    backend::CrosstermBackend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+use super::{
+    apply_offset,
+    editable::{Editable, EditableWidget},
+    HandleRes,
+};
+
+/// Picks one item from a list searched as the user types. The owner fetches
+/// the items matching [Picker::pending_search] and hands them to [Picker::set_items]
+pub struct Picker {
+    pub title: String,
+    query: Editable,
+    /// (value, description) of the items matching `searched`
+    items: Vec<(String, String)>,
+    /// Query the items were fetched for, None before the first search
+    searched: Option<String>,
+    error: String,
+    focus: usize,
+    state: ListState,
+}
+
+impl Picker {
+    pub fn new<S: Into<String>>(title: S) -> Self {
+        Self {
+            title: title.into(),
+            query: Editable::string(""),
+            items: Vec::new(),
+            searched: None,
+            error: String::new(),
+            focus: 0,
+            state: ListState::default(),
+        }
+    }
+
+    /// Returns the query if the items do not match it yet
+    pub fn pending_search(&self) -> Option<String> {
+        let query = self.query.as_str().trim();
+        match self.searched.as_deref() {
+            Some(s) if s == query => None,
+            _ => Some(query.to_string()),
+        }
+    }
+
+    /// Sets the items found for `query`
+    pub fn set_items(&mut self, query: String, items: Vec<(String, String)>) {
+        self.searched = Some(query);
+        self.items = items;
+        self.focus = 0;
+        self.error.clear();
+    }
+
+    /// Shows why the search for `query` failed, it is not retried until the query changes
+    pub fn set_error(&mut self, query: String, error: String) {
+        self.searched = Some(query);
+        self.items.clear();
+        self.error = error;
+    }
+
+    /// Value of the focused item
+    pub fn picked(&self) -> Option<&str> {
+        self.items.get(self.focus).map(|(v, _)| v.as_str())
+    }
+
+    pub fn draw(&mut self, frame: &mut Frame<CrosstermBackend<&mut Stdout>>, rect: Rect) {
+        let rects = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(0)])
+            .split(rect);
+        let mut search = vec![Span::styled(
+            "Search : ",
+            Style::default().fg(Color::DarkGray),
+        )];
+        search.extend(self.query.as_spans(true));
+        if !self.error.is_empty() {
+            search.push(Span::styled(
+                format!("  {}", self.error),
+                Style::default().fg(Color::Red),
+            ));
+        } else if self.pending_search().is_some() {
+            search.push(Span::styled(
+                "  searching...",
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+        frame.render_widget(Paragraph::new(Spans::from(search)), rects[0]);
+
+        let items: Vec<ListItem> = self
+            .items
+            .iter()
+            .map(|(value, desc)| {
+                ListItem::new(Spans::from(vec![
+                    Span::raw(value.as_str()),
+                    Span::styled(format!("  {}", desc), Style::default().fg(Color::DarkGray)),
+                ]))
+            })
+            .collect();
+        self.state.select(if items.is_empty() {
+            None
+        } else {
+            Some(self.focus)
+        });
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(self.title.as_str()),
+            )
+            .highlight_style(Style::default().bg(Color::DarkGray));
+        frame.render_stateful_widget(list, rects[1], &mut self.state);
+    }
+
+    /// Returns Exit(true) once an item is picked, Exit(false) if cancelled
+    pub fn handle_event(&mut self, key: &KeyEvent) -> HandleRes {
+        let amount = match key.code {
+            KeyCode::Down => 1,
+            KeyCode::Up => -1,
+            KeyCode::PageDown => 10,
+            KeyCode::PageUp => -10,
+            KeyCode::Enter if self.picked().is_some() => return HandleRes::Exit(true),
+            KeyCode::Enter => return HandleRes::Handled,
+            KeyCode::Esc => return HandleRes::Exit(false),
+            _ => return self.query.handle_event(key),
+        };
+        let old = self.focus;
+        apply_offset(&mut self.focus, amount, self.items.len());
+        if old != self.focus {
+            HandleRes::ReDraw
+        } else {
+            HandleRes::Handled
+        }
+    }
+}
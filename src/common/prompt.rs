@@ -51,6 +51,20 @@ impl Prompt {
         self.false_button.clear();
         self.cursor = 0;
     }
+
+    /// Resets the prompt into a message with a single [Ok] button
+    pub fn notice(&mut self, msg: &str) {
+        self.clear();
+        self.msg.push_str(msg);
+        self.true_button.push_str("Ok");
+    }
+
+    /// Resets the prompt into an error message with a single [Ok] button
+    pub fn error_notice(&mut self, err: &str) {
+        self.clear();
+        self.error.push_str(err);
+        self.true_button.push_str("Ok");
+    }
 }
 
 impl ViewImpl<()> for Prompt {
@@ -127,14 +141,8 @@ impl ViewImpl<()> for Prompt {
         if !self.fields.is_empty() {
             // how much space we have to draw the fields
             num_fields = self.fields.len();
-            let field_space = std::cmp::min(
-                if rect.height > space_taken {
-                    rect.height - space_taken
-                } else {
-                    0
-                },
-                num_fields as u16,
-            );
+            let fields_height: u16 = self.fields.iter().map(|f| f.1.height()).sum();
+            let field_space = std::cmp::min(rect.height.saturating_sub(space_taken), fields_height);
             constraints.push(Constraint::Length(field_space));
 
             // Set the selected editable field
@@ -145,13 +153,12 @@ impl ViewImpl<()> for Prompt {
             for (idx, r) in self.fields.iter().enumerate() {
                 let (name, val) = (
                     Span::raw(r.0.as_str()),
-                    Spans::from(r.1.as_spans(idx == self.cursor)),
+                    Text::from(r.1.as_lines(idx == self.cursor)),
+                );
+                rows.push(
+                    Row::new([Cell::from(name), Cell::from(":"), Cell::from(val)])
+                        .height(r.1.height()),
                 );
-                rows.push(Row::new([
-                    Cell::from(name),
-                    Cell::from(":"),
-                    Cell::from(val),
-                ]));
 
                 if r.0.len() > max_field_title {
                     max_field_title = r.0.len();
@@ -209,11 +216,12 @@ impl ViewImpl<()> for Prompt {
     fn handle_event(&mut self, event: &Event, _: &mut ()) -> HandleRes {
         // Handle key events
         if let Event::Key(key) = event {
+            let key = &normalize_key(key);
             // If the current focus is an editable field
             // forward the event to it
             if self.cursor < self.fields.len() {
                 let edit_widget = &mut self.fields[self.cursor].1;
-                let r = edit_widget.handle_event(&key);
+                let r = edit_widget.handle_event(key);
                 if matches!(r, HandleRes::ReDraw | HandleRes::Handled) {
                     return r;
                 }
@@ -0,0 +1,300 @@
+use std::io::Stdout;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use tui::{
+    backend::CrosstermBackend,
+    layout::{Constraint, Rect},
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Cell, Row, Table, TableState},
+    Frame,
+};
+
+use super::{
+    apply_offset,
+    editable::{normalize_key, Editable, EditableWidget},
+    HandleRes,
+};
+
+/// A table of editable cells with a focused cell and optional row selection
+#[derive(Default)]
+pub struct EditTable {
+    pub columns: Vec<&'static str>,
+    pub rows: Vec<Vec<Editable>>,
+    /// Whether rows can be selected with [Space]
+    pub selectable: bool,
+    selected: Vec<bool>,
+    pub editing: bool,
+    pub focus_x: usize,
+    pub focus_y: usize,
+    committed: Option<(usize, usize)>,
+    state: TableState,
+}
+
+impl EditTable {
+    pub fn new(columns: &[&'static str]) -> Self {
+        Self {
+            columns: columns.to_vec(),
+            ..Default::default()
+        }
+    }
+
+    /// Allows selecting multiple rows
+    pub fn with_selection(mut self) -> Self {
+        self.selectable = true;
+        self
+    }
+
+    pub fn clear(&mut self) {
+        self.rows.clear();
+        self.selected.clear();
+        self.editing = false;
+        self.focus_y = 0;
+        self.committed = None;
+    }
+
+    pub fn push(&mut self, row: Vec<Editable>) {
+        self.rows.push(row);
+        self.selected.push(false);
+    }
+
+    pub fn remove(&mut self, y: usize) -> Vec<Editable> {
+        self.selected.remove(y);
+        if self.focus_y > 0 && self.focus_y >= y {
+            self.focus_y -= 1;
+        }
+        self.rows.remove(y)
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Index of the focused row
+    pub fn cur_row(&self) -> Option<usize> {
+        if self.focus_y < self.rows.len() {
+            Some(self.focus_y)
+        } else {
+            None
+        }
+    }
+
+    pub fn cur_item(&mut self) -> Option<&mut Editable> {
+        if self.rows.is_empty() {
+            return None;
+        }
+        if self.focus_y >= self.rows.len() {
+            self.focus_y = 0;
+        }
+        let row = &mut self.rows[self.focus_y];
+        if self.focus_x >= row.len() {
+            self.focus_x = 0;
+        }
+        row.get_mut(self.focus_x)
+    }
+
+    pub fn editing_item(&mut self) -> Option<&mut Editable> {
+        if !self.editing {
+            return None;
+        }
+        self.cur_item()
+    }
+
+    /// Returns the (x, y) position of a cell the user just finished editing
+    pub fn take_committed(&mut self) -> Option<(usize, usize)> {
+        self.committed.take()
+    }
+
+    pub fn is_selected(&self, y: usize) -> bool {
+        self.selected.get(y).copied().unwrap_or(false)
+    }
+
+    pub fn set_selected(&mut self, y: usize, selected: bool) {
+        if let Some(s) = self.selected.get_mut(y) {
+            *s = selected;
+        }
+    }
+
+    pub fn select_all(&mut self, selected: bool) {
+        for s in self.selected.iter_mut() {
+            *s = selected;
+        }
+    }
+
+    pub fn num_selected(&self) -> usize {
+        self.selected.iter().filter(|s| **s).count()
+    }
+
+    /// Returns the selected rows or the focused row if nothing is selected
+    pub fn selection(&self) -> Vec<usize> {
+        let r: Vec<usize> = (0..self.rows.len()).filter(|y| self.selected[*y]).collect();
+        if r.is_empty() {
+            return self.cur_row().into_iter().collect();
+        }
+        r
+    }
+
+    pub fn draw(
+        &mut self,
+        frame: &mut Frame<CrosstermBackend<&mut Stdout>>,
+        rect: Rect,
+        block: Block,
+    ) {
+        let marker = if self.selectable { 1 } else { 0 };
+        let mut header_cells = Vec::with_capacity(self.columns.len() + marker);
+        if self.selectable {
+            header_cells.push(Cell::from(" "));
+        }
+        header_cells.extend(self.columns.iter().map(|v| Cell::from(*v)));
+        let header_row = Row::new(header_cells)
+            .height(1)
+            .style(Style::default().fg(Color::DarkGray));
+
+        let mut table_spans = Vec::with_capacity(self.rows.len());
+        let mut widths = vec![Constraint::Length(7); self.columns.len()];
+        for (y, row) in self.rows.iter().enumerate() {
+            let mut items = Vec::with_capacity(row.len() + marker);
+            let is_selected = self.selected[y];
+            if self.selectable {
+                items.push(Spans::from(if is_selected { "*" } else { " " }));
+            }
+            for (x, (i, width)) in row.iter().zip(widths.iter_mut()).enumerate() {
+                let cur_focused = y == self.focus_y && x == self.focus_x;
+                let editing_cur = self.editing && cur_focused;
+                let mut spans = i.as_spans(editing_cur);
+                // Color any changed value
+                if i.is_changed() {
+                    for s in spans.iter_mut() {
+                        s.style = s.style.fg(Color::Yellow);
+                    }
+                } else if is_selected {
+                    for s in spans.iter_mut() {
+                        s.style = s.style.fg(Color::Cyan);
+                    }
+                }
+                let mut width_padding = 2;
+                // Style the current focused item
+                if cur_focused {
+                    width_padding = 0;
+                    let s = if i.is_editable() {
+                        if self.editing {
+                            Style::default()
+                                .fg(Color::Yellow)
+                                .add_modifier(Modifier::BOLD)
+                        } else {
+                            Style::default()
+                                .fg(Color::Green)
+                                .add_modifier(Modifier::BOLD)
+                        }
+                    } else {
+                        Style::default().fg(Color::Black)
+                    };
+                    spans.insert(0, Span::styled("[", s));
+                    spans.push(Span::styled("]", s));
+                }
+                let s = Spans::from(spans);
+                if let Constraint::Length(v) = width {
+                    if s.width() as u16 + width_padding > *v {
+                        *v = s.width() as u16 + width_padding;
+                    }
+                }
+                items.push(s);
+            }
+            table_spans.push(Row::new(items));
+        }
+        if self.selectable {
+            widths.insert(0, Constraint::Length(1));
+        }
+
+        if self.rows.is_empty() {
+            self.state.select(None);
+        } else {
+            self.state.select(Some(self.focus_y));
+        }
+        let table = Table::new(table_spans)
+            .block(block)
+            .header(header_row)
+            .highlight_style(Style::default().bg(Color::DarkGray))
+            .widths(&widths);
+        frame.render_stateful_widget(table, rect, &mut self.state);
+    }
+
+    /// Handles navigation, editing and selection keys
+    pub fn handle_event(&mut self, key: &KeyEvent) -> HandleRes {
+        let key = &normalize_key(key);
+        // Pass keystrokes to editable widget
+        if let Some(i) = self.editing_item() {
+            let r = i.handle_event(key);
+            if matches!(r, HandleRes::ReDraw | HandleRes::Handled) {
+                return r;
+            }
+        }
+
+        let (val, amount, max) = match key.code {
+            KeyCode::Down => (&mut self.focus_y, 1, self.rows.len()),
+            KeyCode::Up => (&mut self.focus_y, -1, self.rows.len()),
+            KeyCode::Right => (&mut self.focus_x, 1, self.columns.len()),
+            KeyCode::Left => (&mut self.focus_x, -1, self.columns.len()),
+            KeyCode::PageDown => (&mut self.focus_y, 5, self.rows.len()),
+            KeyCode::PageUp => (&mut self.focus_y, -5, self.rows.len()),
+            KeyCode::Char(' ') if self.selectable && !self.editing => {
+                if let Some(y) = self.cur_row() {
+                    self.selected[y] = !self.selected[y];
+                    return HandleRes::ReDraw;
+                }
+                return HandleRes::Handled;
+            }
+            KeyCode::Enter => {
+                let (x, y) = (self.focus_x, self.focus_y);
+                if self.editing {
+                    self.editing = false;
+                    if matches!(self.cur_item(), Some(i) if i.is_changed()) {
+                        self.committed = Some((x, y));
+                    }
+                } else if let Some(i) = self.cur_item() {
+                    if !i.is_editable() {
+                        return HandleRes::Handled;
+                    } else if let Editable::Bool(..) = i {
+                        // Bools are toggled in place
+                        i.handle_event(key);
+                        self.committed = Some((x, y));
+                    } else {
+                        self.editing = true;
+                    }
+                }
+                return HandleRes::ReDraw;
+            }
+            KeyCode::Esc if self.editing => {
+                if let Some(s) = self.editing_item() {
+                    s.restore_orig();
+                }
+                self.editing = false;
+                return HandleRes::ReDraw;
+            }
+            _ => return HandleRes::Ignored,
+        };
+
+        let old = *val;
+        apply_offset(val, amount, max);
+        let new = *val;
+        if old == new {
+            return HandleRes::Handled;
+        }
+        // Moving away from a cell discards the pending edit
+        if self.editing {
+            self.editing = false;
+            let (x, y) = match key.code {
+                KeyCode::Left | KeyCode::Right => (old, self.focus_y),
+                _ => (self.focus_x, old),
+            };
+            if let Some(i) = self.rows.get_mut(y).and_then(|r| r.get_mut(x)) {
+                i.restore_orig();
+            }
+        }
+        HandleRes::ReDraw
+    }
+}
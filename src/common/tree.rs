@@ -0,0 +1,266 @@
+use std::io::Stdout;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use tui::{
+    backend::CrosstermBackend,
+    layout::Rect,
+    style::{Color, Style},
+    text::{Span, Spans},
+    widgets::{Block, List, ListItem, ListState},
+    Frame,
+};
+
+use super::{apply_offset, HandleRes};
+
+#[derive(Default)]
+pub struct TreeNode {
+    pub label: String,
+    /// Value attached to the node (e.g. what gets copied)
+    pub data: String,
+    pub children: Vec<TreeNode>,
+    pub expanded: bool,
+    pub marked: bool,
+}
+
+impl TreeNode {
+    pub fn new<S: Into<String>>(label: S) -> Self {
+        Self {
+            label: label.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_data<S: Into<String>>(mut self, data: S) -> Self {
+        self.data = data.into();
+        self
+    }
+
+    pub fn with_children(mut self, children: Vec<TreeNode>) -> Self {
+        self.children = children;
+        self
+    }
+
+    pub fn expanded(mut self) -> Self {
+        self.expanded = true;
+        self
+    }
+
+    /// Builds the tree of a json value, objects and arrays being collapsed.
+    /// The data of every node is its value as compact json
+    pub fn from_json(key: &str, value: &serde_json::Value) -> Self {
+        use serde_json::Value;
+        let prefix = if key.is_empty() {
+            String::new()
+        } else {
+            format!("{} : ", key)
+        };
+        let (label, children) = match value {
+            Value::Object(o) => (
+                format!("{}{{{}}}", prefix, o.len()),
+                o.iter().map(|(k, v)| Self::from_json(k, v)).collect(),
+            ),
+            Value::Array(a) => (
+                format!("{}[{}]", prefix, a.len()),
+                a.iter()
+                    .enumerate()
+                    .map(|(i, v)| Self::from_json(i.to_string().as_str(), v))
+                    .collect(),
+            ),
+            v => (format!("{}{}", prefix, v), Vec::new()),
+        };
+        Self::new(label)
+            .with_data(value.to_string())
+            .with_children(children)
+    }
+
+    /// Calls `f` on this node and all of its descendants
+    pub fn walk<'a, F: FnMut(&'a TreeNode)>(&'a self, f: &mut F) {
+        f(self);
+        for c in self.children.iter() {
+            c.walk(f);
+        }
+    }
+}
+
+/// A list of collapsible nodes with a focused line
+#[derive(Default)]
+pub struct Tree {
+    pub roots: Vec<TreeNode>,
+    /// Whether nodes can be marked with [Space]
+    pub markable: bool,
+    pub focus: usize,
+    state: ListState,
+}
+
+impl Tree {
+    pub fn new(roots: Vec<TreeNode>) -> Self {
+        Self {
+            roots,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the (depth, path) of every visible node
+    pub fn visible(&self) -> Vec<(usize, Vec<usize>)> {
+        fn add(nodes: &[TreeNode], path: &mut Vec<usize>, out: &mut Vec<(usize, Vec<usize>)>) {
+            for (idx, n) in nodes.iter().enumerate() {
+                path.push(idx);
+                out.push((path.len() - 1, path.clone()));
+                if n.expanded {
+                    add(&n.children, path, out);
+                }
+                path.pop();
+            }
+        }
+        let mut out = Vec::new();
+        add(&self.roots, &mut Vec::new(), &mut out);
+        out
+    }
+
+    pub fn node(&self, path: &[usize]) -> Option<&TreeNode> {
+        let mut nodes = &self.roots;
+        let mut cur = None;
+        for idx in path {
+            let n = nodes.get(*idx)?;
+            nodes = &n.children;
+            cur = Some(n);
+        }
+        cur
+    }
+
+    pub fn node_mut(&mut self, path: &[usize]) -> Option<&mut TreeNode> {
+        let (first, rest) = path.split_first()?;
+        let mut cur = self.roots.get_mut(*first)?;
+        for idx in rest {
+            cur = cur.children.get_mut(*idx)?;
+        }
+        Some(cur)
+    }
+
+    /// Path of the focused node
+    pub fn focused_path(&self) -> Option<Vec<usize>> {
+        self.visible().drain(..).nth(self.focus).map(|(_, p)| p)
+    }
+
+    pub fn focused(&self) -> Option<&TreeNode> {
+        self.node(&self.focused_path()?)
+    }
+
+    /// Returns all the marked nodes
+    pub fn marked(&self) -> Vec<&TreeNode> {
+        let mut r = Vec::new();
+        for n in self.roots.iter() {
+            n.walk(&mut |n| {
+                if n.marked {
+                    r.push(n)
+                }
+            });
+        }
+        r
+    }
+
+    pub fn draw(
+        &mut self,
+        frame: &mut Frame<CrosstermBackend<&mut Stdout>>,
+        rect: Rect,
+        block: Block,
+    ) {
+        let visible = self.visible();
+        if self.focus >= visible.len() {
+            self.focus = visible.len().saturating_sub(1);
+        }
+        let items: Vec<ListItem> = visible
+            .iter()
+            .filter_map(|(depth, path)| {
+                let n = self.node(path)?;
+                let arrow = if n.children.is_empty() {
+                    "  "
+                } else if n.expanded {
+                    "▾ "
+                } else {
+                    "▸ "
+                };
+                let mut style = Style::default();
+                if n.marked {
+                    style = style.fg(Color::Cyan);
+                }
+                Some(ListItem::new(Spans::from(vec![
+                    Span::raw("  ".repeat(*depth)),
+                    Span::styled(arrow, Style::default().fg(Color::DarkGray)),
+                    Span::styled(if n.marked { "*" } else { "" }, style),
+                    Span::styled(n.label.clone(), style),
+                ])))
+            })
+            .collect();
+
+        self.state.select(if items.is_empty() {
+            None
+        } else {
+            Some(self.focus)
+        });
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(Style::default().bg(Color::DarkGray));
+        frame.render_stateful_widget(list, rect, &mut self.state);
+    }
+
+    /// Handles navigation, expanding/collapsing and marking keys
+    pub fn handle_event(&mut self, key: &KeyEvent) -> HandleRes {
+        let visible = self.visible();
+        let (amount, max) = match key.code {
+            KeyCode::Down => (1, visible.len()),
+            KeyCode::Up => (-1, visible.len()),
+            KeyCode::PageDown => (10, visible.len()),
+            KeyCode::PageUp => (-10, visible.len()),
+            KeyCode::Home => (-(self.focus as isize), visible.len()),
+            KeyCode::End => (visible.len() as isize, visible.len()),
+            KeyCode::Right | KeyCode::Enter | KeyCode::Left | KeyCode::Char(' ') => {
+                let path = match visible.get(self.focus) {
+                    Some((_, p)) => p.clone(),
+                    None => return HandleRes::Handled,
+                };
+                let markable = self.markable;
+                let n = match self.node_mut(&path) {
+                    Some(n) => n,
+                    None => return HandleRes::Handled,
+                };
+                match key.code {
+                    KeyCode::Char(' ') if markable => n.marked = !n.marked,
+                    KeyCode::Char(' ') => return HandleRes::Ignored,
+                    KeyCode::Enter if !n.children.is_empty() => n.expanded = !n.expanded,
+                    KeyCode::Right if !n.children.is_empty() && !n.expanded => n.expanded = true,
+                    KeyCode::Left if n.expanded => n.expanded = false,
+                    KeyCode::Left if path.len() > 1 => {
+                        // Jump to the parent node
+                        let parent = &path[..path.len() - 1];
+                        if let Some(idx) = visible.iter().position(|(_, p)| p == parent) {
+                            self.focus = idx;
+                        }
+                    }
+                    _ => return HandleRes::Handled,
+                }
+                return HandleRes::ReDraw;
+            }
+            _ => return HandleRes::Ignored,
+        };
+
+        let old = self.focus;
+        apply_offset(&mut self.focus, amount, max);
+        if old != self.focus {
+            HandleRes::ReDraw
+        } else {
+            HandleRes::Handled
+        }
+    }
+
+    /// Expands or collapses every node
+    pub fn expand_all(&mut self, expanded: bool) {
+        fn set(nodes: &mut [TreeNode], expanded: bool) {
+            for n in nodes.iter_mut() {
+                n.expanded = expanded;
+                set(&mut n.children, expanded);
+            }
+        }
+        set(&mut self.roots, expanded);
+    }
+}
@@ -5,7 +5,7 @@ use crossterm::{
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use state::State;
-use std::io::Stdout;
+use std::{io::Stdout, time::Duration};
 use tui::{backend::CrosstermBackend, Terminal};
 
 pub mod common;
@@ -18,6 +18,9 @@ use views::*;
 
 use clap::Parser;
 
+/// How long to wait for events before letting the current view do background work
+const TICK_RATE: Duration = Duration::from_millis(250);
+
 #[derive(Parser)]
 #[clap(
     version = "env!(\"CARGO_PKG_VERSION\")",
@@ -53,6 +56,11 @@ fn raw_mode_main(args: Args, stdout: &mut Stdout) -> Result<(), Box<dyn std::err
     let views: &mut [(bool, &mut dyn ViewImpl<State>)] = &mut [
         (false, &mut HomeView::default()),
         (false, &mut UsersView::default()),
+        (false, &mut RoomsView::default()),
+        (false, &mut MediaView::default()),
+        (false, &mut ReportsView::default()),
+        (false, &mut RegistrationTokensView::default()),
+        (false, &mut FederationView::default()),
     ];
     let backend = Synapse::new(args.host, args.allow_invalid_certs);
     let state = &mut State::from_views(
@@ -61,15 +69,17 @@ fn raw_mode_main(args: Args, stdout: &mut Stdout) -> Result<(), Box<dyn std::err
     );
 
     let mut view_changed = true;
+    let mut view_busy = false;
     loop {
         let (entered_once, cur_view): &mut (bool, &mut dyn ViewImpl<State>) =
             &mut views[state.cur_tab()];
 
         // Call the draw impl
         if view_changed {
+            let hints = cur_view.hints();
             terminal.draw(|f| {
                 // If main layout has an empty content
-                if let Some(content_rect) = state.draw_base(f, f.size()) {
+                if let Some(content_rect) = state.draw_base(f, f.size(), &hints) {
                     if !*entered_once {
                         cur_view.enter_view(state);
                         *entered_once = true;
@@ -80,7 +90,16 @@ fn raw_mode_main(args: Args, stdout: &mut Stdout) -> Result<(), Box<dyn std::err
             })?;
         }
 
-        // Wait for something to happen
+        // Wait for something to happen, letting the view work in the background meanwhile
+        let timeout = if view_busy { Duration::ZERO } else { TICK_RATE };
+        if !event::poll(timeout)? {
+            view_busy = false;
+            if *entered_once && !state.is_prompting() {
+                view_busy = matches!(cur_view.tick(state), HandleRes::ReDraw);
+            }
+            view_changed = view_busy;
+            continue;
+        }
         let evt = event::read()?;
         if let Event::Resize(..) = evt {
             view_changed = true;
@@ -99,6 +118,19 @@ fn raw_mode_main(args: Args, stdout: &mut Stdout) -> Result<(), Box<dyn std::err
         // Forward anything else to the view
         let r = cur_view.handle_event(&evt, state);
         view_changed = matches!(r, HandleRes::ReDraw);
+
+        // The view asked to jump somewhere else
+        if let Some(target) = state.take_goto() {
+            for (idx, (_, v)) in views.iter_mut().enumerate() {
+                if v.goto(&target, state) {
+                    state.set_tab(idx);
+                    break;
+                }
+            }
+            view_changed = true;
+            continue;
+        }
+
         if view_changed || matches!(r, HandleRes::Handled) {
             continue;
         } else if matches!(r, HandleRes::Exit(_)) {
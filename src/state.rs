@@ -12,7 +12,7 @@ use tui::{
 
 use crate::{
     backend::{BackendImpl, Synapse},
-    common::{apply_offset, dec_val, inc_val, prompt::Prompt, HandleRes, ViewImpl},
+    common::{apply_offset, dec_val, inc_val, prompt::Prompt, Goto, HandleRes, ViewImpl},
 };
 
 pub struct State {
@@ -26,6 +26,9 @@ pub struct State {
     layout_with_info: Layout,
     layout_no_info: Layout,
 
+    /// Jump requested by a view, dispatched by the main loop
+    goto: Option<Goto>,
+
     pub backend: Synapse,
 }
 
@@ -52,6 +55,7 @@ impl State {
             layout_no_info: Layout::default()
                 .direction(Direction::Vertical)
                 .constraints([Constraint::Min(3), Constraint::Percentage(100)]),
+            goto: None,
             backend,
         }
     }
@@ -61,21 +65,53 @@ impl State {
         &mut self,
         frame: &mut Frame<CrosstermBackend<&mut Stdout>>,
         rect: Rect,
+        hints: &[(&'static str, &'static str)],
     ) -> Option<Rect> {
-        let mut rects = if self.show_help {
-            &self.layout_with_info
-        } else {
-            &self.layout_no_info
+        let gray = Style::default().fg(Color::DarkGray);
+        let green = Style::default().fg(Color::Green);
+
+        // Wrap the key bindings of the current view on as many lines as needed
+        let mut hint_lines: Vec<Spans> = Vec::new();
+        let mut line: Vec<Span> = Vec::new();
+        let mut line_width = 0;
+        for (key, desc) in hints.iter() {
+            let width = key.len() + desc.len() + 6;
+            if !line.is_empty() && line_width + width > rect.width.saturating_sub(2) as usize {
+                hint_lines.push(Spans::from(std::mem::take(&mut line)));
+                line_width = 0;
+            }
+            line.push(Span::styled(
+                if line.is_empty() { "[" } else { " | [" },
+                gray,
+            ));
+            line.push(Span::styled(*key, green));
+            line.push(Span::styled(format!("] {}", desc), gray));
+            line_width += width;
         }
-        .split(rect);
+        if !line.is_empty() {
+            hint_lines.push(Spans::from(line));
+        }
+
+        let mut rects = if !self.show_help {
+            self.layout_no_info.split(rect)
+        } else if hint_lines.is_empty() {
+            self.layout_with_info.split(rect)
+        } else {
+            Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([
+                    Constraint::Min(3),
+                    Constraint::Length(2 + hint_lines.len() as u16),
+                    Constraint::Percentage(100),
+                ])
+                .split(rect)
+        };
 
         let content_rect = rects.pop().unwrap();
 
         if self.show_help {
-            let gray = Style::default().fg(Color::DarkGray);
-            let green = Style::default().fg(Color::Green);
             let rect = rects.pop().unwrap();
-            let text = Text::from(vec![Spans::from(vec![
+            let mut lines = vec![Spans::from(vec![
                 Span::styled("[", gray),
                 Span::styled("F1", green),
                 Span::styled("] Toggle help  | [", gray),
@@ -83,7 +119,9 @@ impl State {
                 Span::styled("] Back | [", gray),
                 Span::styled("Tab/Shift+Tab", green),
                 Span::styled("] Navigate tabs", gray),
-            ])]);
+            ])];
+            lines.append(&mut hint_lines);
+            let text = Text::from(lines);
             frame.render_widget(
                 Paragraph::new(text)
                     .block(
@@ -177,6 +215,26 @@ impl State {
         self.cur_tab
     }
 
+    pub fn set_tab(&mut self, idx: usize) {
+        if idx < self.tabs.len() {
+            self.cur_tab = idx;
+        }
+    }
+
+    /// Asks the main loop to find a view able to show `target` and switch to it
+    pub fn goto(&mut self, target: Goto) {
+        self.goto = Some(target);
+    }
+
+    pub fn take_goto(&mut self) -> Option<Goto> {
+        self.goto.take()
+    }
+
+    /// Whether the backend is currently asking the user for information
+    pub fn is_prompting(&self) -> bool {
+        self.backend_prompt
+    }
+
     pub fn toggle_help(&mut self) {
         self.show_help = !self.show_help;
     }
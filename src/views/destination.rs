@@ -0,0 +1,264 @@
+use crossterm::event::{Event, KeyCode};
+use tui::{
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph},
+};
+
+use crate::{
+    backend::DestinationV1,
+    common::{
+        editable::{Editable, EditableWidget},
+        human::{format_duration, format_opt_ts, format_ts},
+        prompt::Prompt,
+        table::EditTable,
+        Goto, HandleRes, ViewImpl,
+    },
+    state::State,
+};
+
+#[derive(Default)]
+enum CurPrompt {
+    #[default]
+    None,
+    Notice,
+    ConfirmReset,
+}
+
+const ROOM_COLUMNS: [&str; 2] = ["Room", "Stream ordering"];
+const PAGE_SIZE: usize = 100;
+
+/// Connection state of a destination and the rooms shared with it
+pub struct DestinationView {
+    destination: DestinationV1,
+    cur_prompt: CurPrompt,
+    prompt: Prompt,
+    table: EditTable,
+    /// Number of rooms shared with the destination
+    total: usize,
+    /// Whether the connection was reset, the list needs a reload
+    changed: bool,
+}
+
+impl DestinationView {
+    pub fn new(d: &DestinationV1) -> Self {
+        Self {
+            destination: d.clone(),
+            cur_prompt: CurPrompt::default(),
+            prompt: Prompt::default(),
+            table: EditTable::new(&ROOM_COLUMNS),
+            total: 0,
+            changed: false,
+        }
+    }
+
+    fn reload(&mut self, state: &mut State) {
+        self.table.clear();
+        self.total = 0;
+        self.load_next_chunk(state);
+    }
+
+    fn load_next_chunk(&mut self, state: &mut State) {
+        if !self.table.is_empty() && self.table.len() >= self.total {
+            return;
+        }
+        let page = match state.backend.destination_rooms(
+            self.destination.destination.as_str(),
+            self.table.len(),
+            PAGE_SIZE,
+        ) {
+            Ok(p) => p,
+            Err(e) => {
+                self.prompt.error_notice(e.as_str());
+                self.cur_prompt = CurPrompt::Notice;
+                return;
+            }
+        };
+        self.total = page.total;
+        for r in page.rooms {
+            self.table.push(vec![
+                Editable::ro_string(r.room_id.as_str()),
+                Editable::ro_string(r.stream_ordering.to_string().as_str()),
+            ]);
+        }
+    }
+
+    fn reset_connection(&mut self, state: &mut State) {
+        let d = &mut self.destination;
+        if let Err(e) = state
+            .backend
+            .reset_destination_connection(d.destination.as_str())
+        {
+            self.prompt.error_notice(e.as_str());
+            self.cur_prompt = CurPrompt::Notice;
+            return;
+        }
+        d.retry_last_ts = 0;
+        d.retry_interval = 0;
+        d.failure_ts = None;
+        self.changed = true;
+    }
+}
+
+impl ViewImpl<State> for DestinationView {
+    fn title(&self) -> &'static str {
+        "Destination"
+    }
+
+    fn enter_view(&mut self, state: &mut State) {
+        self.reload(state);
+    }
+
+    fn draw_view(
+        &mut self,
+        frame: &mut tui::Frame<tui::backend::CrosstermBackend<&mut std::io::Stdout>>,
+        rect: tui::layout::Rect,
+        _state: &mut State,
+    ) {
+        if !matches!(self.cur_prompt, CurPrompt::None) {
+            self.prompt.draw_view(frame, rect, &mut ());
+            return;
+        }
+
+        let d = &self.destination;
+        let field = |name: &str, value: String| {
+            Spans::from(vec![
+                Span::styled(
+                    format!("{:>20} : ", name),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::raw(value),
+            ])
+        };
+        let lines = vec![
+            field(
+                "Last retry",
+                if d.retry_last_ts > 0 {
+                    format_ts(d.retry_last_ts)
+                } else {
+                    "-".into()
+                },
+            ),
+            field(
+                "Retry interval",
+                if d.retry_interval > 0 {
+                    format_duration(d.retry_interval)
+                } else {
+                    "-".into()
+                },
+            ),
+            field("Failing since", format_opt_ts(d.failure_ts)),
+            field(
+                "Last sent stream",
+                d.last_successful_stream_ordering
+                    .map(|s| s.to_string())
+                    .unwrap_or_else(|| "-".into()),
+            ),
+        ];
+
+        let rects = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(lines.len() as u16 + 2),
+                Constraint::Min(0),
+            ])
+            .split(rect);
+        frame.render_widget(
+            Paragraph::new(lines).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(d.destination.as_str()),
+            ),
+            rects[0],
+        );
+        self.table.draw(
+            frame,
+            rects[1],
+            Block::default().borders(Borders::ALL).title(format!(
+                "{}/{} shared rooms",
+                self.table.len(),
+                self.total
+            )),
+        );
+    }
+
+    fn handle_event(&mut self, event: &Event, state: &mut State) -> HandleRes {
+        match self.cur_prompt {
+            CurPrompt::Notice => {
+                let r = self.prompt.handle_event(event, &mut ());
+                if let HandleRes::Exit(_) = r {
+                    self.cur_prompt = CurPrompt::None;
+                    return HandleRes::ReDraw;
+                }
+                return r;
+            }
+            CurPrompt::ConfirmReset => {
+                let r = self.prompt.handle_event(event, &mut ());
+                if let HandleRes::Exit(confirmed) = r {
+                    self.cur_prompt = CurPrompt::None;
+                    if confirmed {
+                        self.reset_connection(state);
+                    }
+                    return HandleRes::ReDraw;
+                }
+                return r;
+            }
+            CurPrompt::None => {}
+        }
+
+        let key = match event {
+            Event::Key(k) => k,
+            _ => return HandleRes::Ignored,
+        };
+
+        if let KeyCode::Enter = key.code {
+            if let Some(y) = self.table.cur_row() {
+                state.goto(Goto::Room(self.table.rows[y][0].as_str().to_string()));
+            }
+            return HandleRes::ReDraw;
+        }
+
+        let r = self.table.handle_event(key);
+        if !matches!(r, HandleRes::Ignored) {
+            if self.table.focus_y + 1 >= self.table.len() {
+                self.load_next_chunk(state);
+            }
+            return r;
+        }
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => HandleRes::Exit(self.changed),
+            KeyCode::F(5) => {
+                self.reload(state);
+                HandleRes::ReDraw
+            }
+            KeyCode::Char('r') => {
+                confirm_reset(&mut self.prompt, self.destination.destination.as_str());
+                self.cur_prompt = CurPrompt::ConfirmReset;
+                HandleRes::ReDraw
+            }
+            _ => HandleRes::Ignored,
+        }
+    }
+
+    fn hints(&self) -> Vec<(&'static str, &'static str)> {
+        vec![
+            ("Enter", "Go to room"),
+            ("r", "Reset connection"),
+            ("F5", "Reload"),
+            ("Esc", "Back"),
+        ]
+    }
+}
+
+/// Asks to reset the connection to `destination`
+pub(super) fn confirm_reset(prompt: &mut Prompt, destination: &str) {
+    prompt.clear();
+    prompt.msg = format!(
+        "Reset the connection to {} ?\nThe retry backoff is cleared and pending events are sent right away",
+        destination
+    );
+    prompt.true_button.push_str("Reset");
+    prompt.false_button.push_str("Cancel");
+}
@@ -0,0 +1,257 @@
+use crossterm::event::{Event, KeyCode};
+use serde_json::Value;
+use tui::{
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::Spans,
+    widgets::{Block, Borders, Paragraph, Tabs},
+};
+
+use super::{room_detail::state_event_node, room_messages::event_row};
+use crate::{
+    common::{prompt::Prompt, table::EditTable, tree::Tree, Goto, HandleRes, ViewImpl},
+    state::State,
+};
+
+#[derive(Default)]
+enum CurPrompt {
+    #[default]
+    None,
+    Notice,
+}
+
+/// Parts of the context shown one at a time, cycled with `[` and `]`
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Section {
+    Timeline,
+    Event,
+    State,
+}
+const SECTIONS: [(Section, &str); 3] = [
+    (Section::Timeline, "Timeline"),
+    (Section::Event, "Event"),
+    (Section::State, "State"),
+];
+
+const TIMELINE_COLUMNS: [&str; 4] = ["Sender", "Time", "Type", "Body"];
+/// Number of events fetched around the event
+const CONTEXT_SIZE: usize = 20;
+
+/// An event with the events around it and the room state at that point
+pub struct EventContextView {
+    room_id: String,
+    event_id: String,
+    cur_prompt: CurPrompt,
+    prompt: Prompt,
+    section: Section,
+    /// Events before, the event and events after, oldest first
+    events: Vec<Value>,
+    table: EditTable,
+    state: Tree,
+    /// Scroll of the event json
+    scroll: u16,
+}
+
+impl EventContextView {
+    pub fn new(room_id: &str, event_id: &str) -> Self {
+        Self {
+            room_id: room_id.to_string(),
+            event_id: event_id.to_string(),
+            cur_prompt: CurPrompt::default(),
+            prompt: Prompt::default(),
+            section: Section::Timeline,
+            events: Vec::new(),
+            table: EditTable::new(&TIMELINE_COLUMNS),
+            state: Tree::default(),
+            scroll: 0,
+        }
+    }
+
+    fn reload(&mut self, state: &mut State) {
+        let ctx = match state.backend.event_context(
+            self.room_id.as_str(),
+            self.event_id.as_str(),
+            CONTEXT_SIZE,
+        ) {
+            Ok(c) => c,
+            Err(e) => {
+                self.prompt.error_notice(e.as_str());
+                self.cur_prompt = CurPrompt::Notice;
+                return;
+            }
+        };
+        self.events = ctx.events_before.into_iter().rev().collect();
+        let focus = self.events.len();
+        self.events.push(ctx.event);
+        self.events.extend(ctx.events_after);
+        self.table.clear();
+        for e in self.events.iter() {
+            self.table.push(event_row(e));
+        }
+        self.table.focus_y = focus;
+        self.state = Tree::new(ctx.state.iter().map(state_event_node).collect());
+        self.scroll = 0;
+    }
+
+    /// Event focused in the timeline
+    fn cur_event(&self) -> Option<&Value> {
+        self.table.cur_row().map(|y| &self.events[y])
+    }
+
+    fn switch_section(&mut self, offset: isize) {
+        let idx = SECTIONS
+            .iter()
+            .position(|(s, _)| *s == self.section)
+            .unwrap_or(0) as isize;
+        let len = SECTIONS.len() as isize;
+        self.section = SECTIONS[((idx + offset + len) % len) as usize].0;
+    }
+}
+
+impl ViewImpl<State> for EventContextView {
+    fn title(&self) -> &'static str {
+        "Event context"
+    }
+
+    fn enter_view(&mut self, state: &mut State) {
+        self.reload(state);
+    }
+
+    fn draw_view(
+        &mut self,
+        frame: &mut tui::Frame<tui::backend::CrosstermBackend<&mut std::io::Stdout>>,
+        rect: tui::layout::Rect,
+        _state: &mut State,
+    ) {
+        if !matches!(self.cur_prompt, CurPrompt::None) {
+            self.prompt.draw_view(frame, rect, &mut ());
+            return;
+        }
+
+        let rects = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(0)])
+            .split(rect);
+        let selected = SECTIONS
+            .iter()
+            .position(|(s, _)| *s == self.section)
+            .unwrap_or(0);
+        let titles: Vec<Spans> = SECTIONS.iter().map(|(_, t)| Spans::from(*t)).collect();
+        frame.render_widget(
+            Tabs::new(titles)
+                .select(selected)
+                .style(Style::default().fg(Color::DarkGray))
+                .highlight_style(
+                    Style::default()
+                        .fg(Color::White)
+                        .add_modifier(Modifier::BOLD),
+                )
+                .divider("|"),
+            rects[0],
+        );
+
+        let block = Block::default().borders(Borders::ALL);
+        match self.section {
+            Section::Timeline => {
+                let title = format!("{} : around {}", self.room_id, self.event_id);
+                self.table.draw(frame, rects[1], block.title(title));
+            }
+            Section::Event => {
+                let event = self.cur_event().cloned().unwrap_or_default();
+                let json = serde_json::to_string_pretty(&event).unwrap_or_default();
+                let lines: Vec<Spans> = json.lines().map(|l| Spans::from(l.to_string())).collect();
+                let title = event["event_id"].as_str().unwrap_or("").to_string();
+                frame.render_widget(
+                    Paragraph::new(lines)
+                        .scroll((self.scroll, 0))
+                        .block(block.title(title)),
+                    rects[1],
+                );
+            }
+            Section::State => {
+                let title = format!("{} state events", self.state.roots.len());
+                self.state.draw(frame, rects[1], block.title(title));
+            }
+        }
+    }
+
+    fn handle_event(&mut self, event: &Event, state: &mut State) -> HandleRes {
+        if let CurPrompt::Notice = self.cur_prompt {
+            let r = self.prompt.handle_event(event, &mut ());
+            if let HandleRes::Exit(_) = r {
+                self.cur_prompt = CurPrompt::None;
+                return HandleRes::ReDraw;
+            }
+            return r;
+        }
+
+        let key = match event {
+            Event::Key(k) => k,
+            _ => return HandleRes::Ignored,
+        };
+
+        match (self.section, key.code) {
+            (_, KeyCode::Esc | KeyCode::Char('q')) => return HandleRes::Exit(false),
+            (_, KeyCode::F(5)) => {
+                self.reload(state);
+                return HandleRes::ReDraw;
+            }
+            (_, KeyCode::Char(']')) => {
+                self.switch_section(1);
+                return HandleRes::ReDraw;
+            }
+            (_, KeyCode::Char('[')) => {
+                self.switch_section(-1);
+                return HandleRes::ReDraw;
+            }
+            (_, KeyCode::Char('g')) => {
+                state.goto(Goto::Room(self.room_id.clone()));
+                return HandleRes::ReDraw;
+            }
+            (Section::Timeline, KeyCode::Enter) => {
+                // Center the context on the focused event
+                let event_id = self.cur_event().and_then(|e| e["event_id"].as_str());
+                if let Some(event_id) = event_id {
+                    self.event_id = event_id.to_string();
+                    self.reload(state);
+                }
+                return HandleRes::ReDraw;
+            }
+            (Section::Timeline, _) => {
+                let r = self.table.handle_event(key);
+                if matches!(r, HandleRes::ReDraw) {
+                    self.scroll = 0;
+                }
+                return r;
+            }
+            (Section::State, KeyCode::Char('e')) => {
+                let expanded = self.state.roots.iter().any(|n| n.expanded);
+                self.state.expand_all(!expanded);
+                return HandleRes::ReDraw;
+            }
+            (Section::State, _) => return self.state.handle_event(key),
+            (Section::Event, _) => {}
+        }
+
+        match key.code {
+            KeyCode::Up => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::Down => self.scroll = self.scroll.saturating_add(1),
+            KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(10),
+            KeyCode::PageDown => self.scroll = self.scroll.saturating_add(10),
+            KeyCode::Home => self.scroll = 0,
+            _ => return HandleRes::Ignored,
+        }
+        HandleRes::ReDraw
+    }
+
+    fn hints(&self) -> Vec<(&'static str, &'static str)> {
+        let mut hints = vec![("[/]", "Switch section")];
+        match self.section {
+            Section::Timeline => hints.push(("Enter", "Center on event")),
+            Section::Event => hints.push(("↑/↓", "Scroll")),
+            Section::State => hints.extend([("Enter/←/→", "Collapse/Expand"), ("e", "Expand all")]),
+        }
+        hints.extend([("g", "Go to room"), ("F5", "Reload"), ("Esc", "Back")]);
+        hints
+    }
+}
@@ -0,0 +1,322 @@
+use crossterm::event::{Event, KeyCode};
+use tui::{
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Style},
+    text::Span,
+    widgets::{Block, Borders, Paragraph},
+};
+
+use super::destination::confirm_reset;
+use crate::{
+    backend::{DestinationV1, Synapse},
+    common::{
+        editable::{Editable, EditableWidget},
+        human::{format_age, format_duration, now_ms},
+        prompt::Prompt,
+        table::EditTable,
+        HandleRes, ViewImpl,
+    },
+    state::State,
+    views::DestinationView,
+};
+
+#[derive(Default)]
+enum CurPrompt {
+    #[default]
+    None,
+    Notice,
+    Filter,
+    ConfirmReset,
+}
+
+const DESTINATION_COLUMNS: [&str; 5] = [
+    "Destination",
+    "Last retry",
+    "Retry interval",
+    "Failing since",
+    "Last sent stream",
+];
+const PAGE_SIZE: usize = 100;
+
+/// Lists the servers we federate with and their connection state
+pub struct FederationView {
+    cur_prompt: CurPrompt,
+    prompt: Prompt,
+    table: EditTable,
+    destinations: Vec<DestinationV1>,
+    /// Number of destinations on the server
+    total: usize,
+    /// Only destinations whose name contains this
+    search: String,
+    /// View opened on top of the destination list
+    sub_view: Option<Box<dyn ViewImpl<State>>>,
+}
+
+impl Default for FederationView {
+    fn default() -> Self {
+        Self {
+            cur_prompt: CurPrompt::default(),
+            prompt: Prompt::default(),
+            table: EditTable::new(&DESTINATION_COLUMNS),
+            destinations: Vec::new(),
+            total: 0,
+            search: String::new(),
+            sub_view: None,
+        }
+    }
+}
+
+impl FederationView {
+    fn reload(&mut self, synapse: &mut Synapse) {
+        self.table.clear();
+        self.destinations.clear();
+        self.total = 0;
+        self.load_next_chunk(synapse);
+    }
+
+    fn load_next_chunk(&mut self, synapse: &mut Synapse) {
+        if !self.destinations.is_empty() && self.destinations.len() >= self.total {
+            return;
+        }
+        let page = match synapse.list_destinations(
+            self.destinations.len(),
+            PAGE_SIZE,
+            self.search.as_str(),
+        ) {
+            Ok(p) => p,
+            Err(e) => {
+                self.prompt.error_notice(e.as_str());
+                self.cur_prompt = CurPrompt::Notice;
+                return;
+            }
+        };
+        self.total = page.total;
+        let now = now_ms();
+        for d in page.destinations {
+            self.table.push(destination_row(&d, now));
+            self.destinations.push(d);
+        }
+    }
+
+    fn cur_destination(&self) -> Option<String> {
+        self.table
+            .cur_row()
+            .map(|y| self.destinations[y].destination.clone())
+    }
+
+    fn status_line(&self) -> String {
+        let failing = self
+            .destinations
+            .iter()
+            .filter(|d| d.failure_ts.is_some())
+            .count();
+        let mut r = format!(
+            "{}/{} destinations, {} failing",
+            self.destinations.len(),
+            self.total,
+            failing
+        );
+        if !self.search.is_empty() {
+            r.push_str(&format!(" | contains '{}'", self.search));
+        }
+        r
+    }
+}
+
+/// Row of the destination table, `now` being used for the ages
+fn destination_row(d: &DestinationV1, now: u64) -> Vec<Editable> {
+    let retry = if d.retry_last_ts > 0 {
+        format_age(d.retry_last_ts, now)
+    } else {
+        "-".into()
+    };
+    let interval = if d.retry_interval > 0 {
+        format_duration(d.retry_interval)
+    } else {
+        "-".into()
+    };
+    vec![
+        Editable::ro_string(d.destination.as_str()),
+        Editable::ro_string(retry.as_str()),
+        Editable::ro_string(interval.as_str()),
+        Editable::ro_string(
+            d.failure_ts
+                .map(|ts| format_age(ts, now))
+                .unwrap_or_default()
+                .as_str(),
+        ),
+        Editable::ro_string(
+            d.last_successful_stream_ordering
+                .map(|s| s.to_string())
+                .unwrap_or_default()
+                .as_str(),
+        ),
+    ]
+}
+
+impl ViewImpl<State> for FederationView {
+    fn title(&self) -> &'static str {
+        "Federation"
+    }
+
+    fn enter_view(&mut self, state: &mut State) {
+        self.load_next_chunk(&mut state.backend);
+    }
+
+    fn draw_view(
+        &mut self,
+        frame: &mut tui::Frame<tui::backend::CrosstermBackend<&mut std::io::Stdout>>,
+        rect: tui::layout::Rect,
+        state: &mut State,
+    ) {
+        if let Some(v) = self.sub_view.as_mut() {
+            v.draw_view(frame, rect, state);
+            return;
+        }
+        if !matches!(self.cur_prompt, CurPrompt::None) {
+            self.prompt.draw_view(frame, rect, &mut ());
+            return;
+        }
+
+        let rects = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(0)])
+            .split(rect);
+        frame.render_widget(
+            Paragraph::new(Span::styled(
+                self.status_line(),
+                Style::default().fg(Color::Yellow),
+            )),
+            rects[0],
+        );
+        self.table.draw(
+            frame,
+            rects[1],
+            Block::default().borders(Borders::LEFT | Borders::RIGHT),
+        );
+    }
+
+    fn handle_event(&mut self, event: &Event, state: &mut State) -> HandleRes {
+        if let Some(v) = self.sub_view.as_mut() {
+            let r = v.handle_event(event, state);
+            if let HandleRes::Exit(changed) = r {
+                self.sub_view = None;
+                if changed {
+                    self.reload(&mut state.backend);
+                }
+                return HandleRes::ReDraw;
+            }
+            return r;
+        }
+
+        match self.cur_prompt {
+            CurPrompt::Notice => {
+                let r = self.prompt.handle_event(event, &mut ());
+                if let HandleRes::Exit(_) = r {
+                    self.cur_prompt = CurPrompt::None;
+                    return HandleRes::ReDraw;
+                }
+                return r;
+            }
+            CurPrompt::Filter => {
+                let r = self.prompt.handle_event(event, &mut ());
+                if let HandleRes::Exit(apply) = r {
+                    self.cur_prompt = CurPrompt::None;
+                    if apply {
+                        self.search = self.prompt.fields[0].1.as_str().trim().to_string();
+                        self.reload(&mut state.backend);
+                    }
+                    return HandleRes::ReDraw;
+                }
+                return r;
+            }
+            CurPrompt::ConfirmReset => {
+                let r = self.prompt.handle_event(event, &mut ());
+                if let HandleRes::Exit(confirmed) = r {
+                    self.cur_prompt = CurPrompt::None;
+                    if let (true, Some(d)) = (confirmed, self.cur_destination()) {
+                        match state.backend.reset_destination_connection(d.as_str()) {
+                            Ok(_) => self.reload(&mut state.backend),
+                            Err(e) => {
+                                self.prompt.error_notice(e.as_str());
+                                self.cur_prompt = CurPrompt::Notice;
+                            }
+                        }
+                    }
+                    return HandleRes::ReDraw;
+                }
+                return r;
+            }
+            CurPrompt::None => {}
+        }
+
+        let key = match event {
+            Event::Key(k) => k,
+            _ => return HandleRes::Ignored,
+        };
+
+        if let KeyCode::Enter = key.code {
+            if let Some(d) = self.table.cur_row().map(|y| &self.destinations[y]) {
+                let mut view = DestinationView::new(d);
+                view.enter_view(state);
+                self.sub_view = Some(Box::new(view));
+            }
+            return HandleRes::ReDraw;
+        }
+
+        let r = self.table.handle_event(key);
+        if !matches!(r, HandleRes::Ignored) {
+            // Fetch more destinations when reaching the end of the list
+            if self.table.focus_y + 1 >= self.table.len() {
+                self.load_next_chunk(&mut state.backend);
+            }
+            return r;
+        }
+
+        match key.code {
+            KeyCode::F(5) => {
+                self.reload(&mut state.backend);
+                HandleRes::ReDraw
+            }
+            KeyCode::Char('r') => {
+                if let Some(d) = self.cur_destination() {
+                    confirm_reset(&mut self.prompt, d.as_str());
+                    self.cur_prompt = CurPrompt::ConfirmReset;
+                }
+                HandleRes::ReDraw
+            }
+            KeyCode::Char('/') => {
+                self.prompt.clear();
+                self.prompt.msg.push_str("Filter destinations");
+                self.prompt.fields.push((
+                    "Server name contains".into(),
+                    Editable::string(self.search.as_str()),
+                ));
+                self.prompt.true_button.push_str("Apply");
+                self.prompt.false_button.push_str("Cancel");
+                self.cur_prompt = CurPrompt::Filter;
+                HandleRes::ReDraw
+            }
+            _ => HandleRes::Ignored,
+        }
+    }
+
+    fn tick(&mut self, state: &mut State) -> HandleRes {
+        if let Some(v) = self.sub_view.as_mut() {
+            return v.tick(state);
+        }
+        HandleRes::Ignored
+    }
+
+    fn hints(&self) -> Vec<(&'static str, &'static str)> {
+        if let Some(v) = self.sub_view.as_ref() {
+            return v.hints();
+        }
+        vec![
+            ("Enter", "Shared rooms"),
+            ("r", "Reset connection"),
+            ("/", "Filter"),
+            ("F5", "Reload"),
+        ]
+    }
+}
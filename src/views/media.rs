@@ -0,0 +1,438 @@
+use crossterm::event::{Event, KeyCode};
+use tui::{
+    style::{Color, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph, Wrap},
+};
+
+use crate::{
+    backend::{parse_mxc, MediaStatsQuery, OldMediaQuery, Synapse},
+    common::{
+        editable::{Editable, EditableWidget},
+        human::{format_age, format_bytes, format_ts, now_ms, parse_size, parse_time},
+        prompt::Prompt,
+        HandleRes, ViewImpl,
+    },
+    state::State,
+    views::MediaStatsView,
+};
+
+/// Actions taking a single media, room or user as input
+#[derive(Clone, Copy)]
+enum MediaAction {
+    Quarantine,
+    Unquarantine,
+    Protect,
+    Unprotect,
+    QuarantineRoom,
+    QuarantineUser,
+}
+impl MediaAction {
+    fn from_key(key: KeyCode) -> Option<Self> {
+        Some(match key {
+            KeyCode::Char('m') => Self::Quarantine,
+            KeyCode::Char('M') => Self::Unquarantine,
+            KeyCode::Char('p') => Self::Protect,
+            KeyCode::Char('P') => Self::Unprotect,
+            KeyCode::Char('r') => Self::QuarantineRoom,
+            KeyCode::Char('u') => Self::QuarantineUser,
+            _ => return None,
+        })
+    }
+
+    fn desc(&self) -> &'static str {
+        match self {
+            Self::Quarantine => "Quarantine a media",
+            Self::Unquarantine => "Lift the quarantine of a media",
+            Self::Protect => "Protect a local media from quarantine",
+            Self::Unprotect => "Remove the quarantine protection of a local media",
+            Self::QuarantineRoom => "Quarantine all the media of a room",
+            Self::QuarantineUser => "Quarantine all the media uploaded by a user",
+        }
+    }
+
+    fn field(&self) -> &'static str {
+        match self {
+            Self::QuarantineRoom => "Room id",
+            Self::QuarantineUser => "User id",
+            _ => "Media (mxc://server/id)",
+        }
+    }
+
+    /// Applies the action and returns a line describing the result
+    fn apply(&self, synapse: &mut Synapse, input: &str) -> Result<String, String> {
+        match self {
+            Self::Quarantine | Self::Unquarantine => {
+                let local = synapse.server_name().to_string();
+                let (server, media_id) = parse_mxc(input, local.as_str())?;
+                let quarantine = matches!(self, Self::Quarantine);
+                synapse.quarantine_media(server, media_id, quarantine)?;
+                Ok(format!(
+                    "mxc://{}/{} {}",
+                    server,
+                    media_id,
+                    if quarantine {
+                        "quarantined"
+                    } else {
+                        "released from quarantine"
+                    }
+                ))
+            }
+            Self::Protect | Self::Unprotect => {
+                let local = synapse.server_name().to_string();
+                let (server, media_id) = parse_mxc(input, local.as_str())?;
+                if server != local {
+                    return Err(format!("Only media of {} can be protected", local));
+                }
+                let protect = matches!(self, Self::Protect);
+                synapse.protect_media(media_id, protect)?;
+                Ok(format!(
+                    "mxc://{}/{} {}",
+                    server,
+                    media_id,
+                    if protect { "protected" } else { "unprotected" }
+                ))
+            }
+            Self::QuarantineRoom => {
+                let n = synapse.quarantine_room_media(input)?;
+                Ok(format!("{} media of {} quarantined", n, input))
+            }
+            Self::QuarantineUser => {
+                let n = synapse.quarantine_user_media(input)?;
+                Ok(format!("{} media of {} quarantined", n, input))
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+enum CurPrompt {
+    #[default]
+    None,
+    Notice,
+    Action(MediaAction),
+    PurgeCache,
+    /// Purge the remote media not accessed since then
+    ConfirmPurgeCache(u64),
+    DeleteOld,
+    ConfirmDeleteOld(OldMediaQuery),
+}
+
+/// Server wide media moderation
+#[derive(Default)]
+pub struct MediaView {
+    cur_prompt: CurPrompt,
+    prompt: Prompt,
+    /// Results of the actions applied so far, most recent last
+    log: Vec<String>,
+    /// View opened on top of the results
+    sub_view: Option<Box<dyn ViewImpl<State>>>,
+}
+
+impl ViewImpl<State> for MediaView {
+    fn title(&self) -> &'static str {
+        "Media"
+    }
+
+    fn draw_view(
+        &mut self,
+        frame: &mut tui::Frame<tui::backend::CrosstermBackend<&mut std::io::Stdout>>,
+        rect: tui::layout::Rect,
+        state: &mut State,
+    ) {
+        if let Some(v) = self.sub_view.as_mut() {
+            v.draw_view(frame, rect, state);
+            return;
+        }
+
+        if !matches!(self.cur_prompt, CurPrompt::None) {
+            self.prompt.draw_view(frame, rect, &mut ());
+            return;
+        }
+
+        let height = rect.height.saturating_sub(2) as usize;
+        let lines: Vec<Spans> = self
+            .log
+            .iter()
+            .skip(self.log.len().saturating_sub(height))
+            .map(|line| {
+                Spans::from(Span::styled(
+                    line.as_str(),
+                    Style::default().fg(Color::Green),
+                ))
+            })
+            .collect();
+        frame.render_widget(
+            Paragraph::new(lines)
+                .wrap(Wrap { trim: false })
+                .block(Block::default().borders(Borders::ALL).title("Results")),
+            rect,
+        );
+    }
+
+    fn handle_event(&mut self, event: &Event, state: &mut State) -> HandleRes {
+        if let Some(v) = self.sub_view.as_mut() {
+            let r = v.handle_event(event, state);
+            if let HandleRes::Exit(_) = r {
+                self.sub_view = None;
+                return HandleRes::ReDraw;
+            }
+            return r;
+        }
+
+        match self.cur_prompt {
+            CurPrompt::None => {}
+            CurPrompt::Notice => {
+                let r = self.prompt.handle_event(event, &mut ());
+                if let HandleRes::Exit(_) = r {
+                    self.cur_prompt = CurPrompt::None;
+                    return HandleRes::ReDraw;
+                }
+                return r;
+            }
+            CurPrompt::Action(action) => {
+                let r = self.prompt.handle_event(event, &mut ());
+                if let HandleRes::Exit(confirmed) = r {
+                    if confirmed {
+                        let input = self.prompt.fields[0].1.as_str().trim().to_string();
+                        if input.is_empty() {
+                            self.prompt.error = format!("{} is required", action.field());
+                            return HandleRes::ReDraw;
+                        }
+                        match action.apply(&mut state.backend, input.as_str()) {
+                            Ok(line) => self.log.push(line),
+                            Err(e) => {
+                                self.prompt.error = e;
+                                return HandleRes::ReDraw;
+                            }
+                        }
+                    }
+                    self.cur_prompt = CurPrompt::None;
+                    return HandleRes::ReDraw;
+                }
+                return r;
+            }
+            CurPrompt::PurgeCache => {
+                let r = self.prompt.handle_event(event, &mut ());
+                if let HandleRes::Exit(confirmed) = r {
+                    self.cur_prompt = CurPrompt::None;
+                    if confirmed {
+                        match parse_time(self.prompt.fields[0].1.as_str(), now_ms()) {
+                            Ok(before_ts) => self.confirm_purge_cache(before_ts),
+                            Err(e) => {
+                                self.prompt.error = e;
+                                self.cur_prompt = CurPrompt::PurgeCache;
+                            }
+                        }
+                    }
+                    return HandleRes::ReDraw;
+                }
+                return r;
+            }
+            CurPrompt::ConfirmPurgeCache(before_ts) => {
+                let r = self.prompt.handle_event(event, &mut ());
+                if let HandleRes::Exit(confirmed) = r {
+                    self.cur_prompt = CurPrompt::None;
+                    if confirmed {
+                        match state.backend.purge_media_cache(before_ts) {
+                            Ok(n) => self.log.push(format!(
+                                "{} cached remote media not accessed since {} purged",
+                                n,
+                                format_ts(before_ts)
+                            )),
+                            Err(e) => {
+                                self.prompt.error_notice(e.as_str());
+                                self.cur_prompt = CurPrompt::Notice;
+                            }
+                        }
+                    }
+                    return HandleRes::ReDraw;
+                }
+                return r;
+            }
+            CurPrompt::DeleteOld => {
+                let r = self.prompt.handle_event(event, &mut ());
+                if let HandleRes::Exit(confirmed) = r {
+                    self.cur_prompt = CurPrompt::None;
+                    if confirmed {
+                        match self.old_media_query() {
+                            Ok(query) => self.confirm_delete_old(query, &mut state.backend),
+                            Err(e) => {
+                                self.prompt.error = e;
+                                self.cur_prompt = CurPrompt::DeleteOld;
+                            }
+                        }
+                    }
+                    return HandleRes::ReDraw;
+                }
+                return r;
+            }
+            CurPrompt::ConfirmDeleteOld(ref query) => {
+                let r = self.prompt.handle_event(event, &mut ());
+                if let HandleRes::Exit(confirmed) = r {
+                    if confirmed {
+                        match state.backend.delete_old_media(query) {
+                            Ok(n) => self.log.push(format!(
+                                "{} local media not accessed since {} deleted",
+                                n,
+                                format_ts(query.before_ts)
+                            )),
+                            Err(e) => {
+                                self.prompt.error_notice(e.as_str());
+                                self.cur_prompt = CurPrompt::Notice;
+                                return HandleRes::ReDraw;
+                            }
+                        }
+                    }
+                    self.cur_prompt = CurPrompt::None;
+                    return HandleRes::ReDraw;
+                }
+                return r;
+            }
+        }
+
+        let key = match event {
+            Event::Key(k) => k,
+            _ => return HandleRes::Ignored,
+        };
+
+        if let Some(action) = MediaAction::from_key(key.code) {
+            self.prompt.clear();
+            self.prompt.msg.push_str(action.desc());
+            self.prompt
+                .fields
+                .push((action.field().into(), Editable::string("")));
+            self.prompt.true_button.push_str("Apply");
+            self.prompt.false_button.push_str("Cancel");
+            self.cur_prompt = CurPrompt::Action(action);
+            return HandleRes::ReDraw;
+        }
+
+        match key.code {
+            KeyCode::Char('s') => {
+                let mut view = Box::<MediaStatsView>::default();
+                view.enter_view(state);
+                self.sub_view = Some(view);
+                HandleRes::ReDraw
+            }
+            KeyCode::Char('c') => {
+                self.prompt.clear();
+                self.prompt
+                    .msg
+                    .push_str("Purge the remote media cache\nDate (YYYY-MM-DD [HH:MM], UTC) or age (30d, 2w, 12h)");
+                self.prompt
+                    .fields
+                    .push(("Not accessed since".into(), Editable::string("30d")));
+                self.prompt.true_button.push_str("Next");
+                self.prompt.false_button.push_str("Cancel");
+                self.cur_prompt = CurPrompt::PurgeCache;
+                HandleRes::ReDraw
+            }
+            KeyCode::Char('o') => {
+                self.prompt.clear();
+                self.prompt
+                    .msg
+                    .push_str("Delete old local media\nDate (YYYY-MM-DD [HH:MM], UTC) or age (90d, 1y), size like 50MB");
+                self.prompt
+                    .fields
+                    .push(("Not accessed since".into(), Editable::string("1y")));
+                self.prompt
+                    .fields
+                    .push(("Larger than".into(), Editable::string("0")));
+                self.prompt
+                    .fields
+                    .push(("Keep profile pictures".into(), Editable::bool(true)));
+                self.prompt.true_button.push_str("Next");
+                self.prompt.false_button.push_str("Cancel");
+                self.cur_prompt = CurPrompt::DeleteOld;
+                HandleRes::ReDraw
+            }
+            _ => HandleRes::Ignored,
+        }
+    }
+
+    fn tick(&mut self, state: &mut State) -> HandleRes {
+        match self.sub_view.as_mut() {
+            Some(v) => v.tick(state),
+            None => HandleRes::Ignored,
+        }
+    }
+
+    fn hints(&self) -> Vec<(&'static str, &'static str)> {
+        if let Some(v) = self.sub_view.as_ref() {
+            return v.hints();
+        }
+        vec![
+            ("m/M", "Quarantine media/Lift"),
+            ("p/P", "Protect/Unprotect media"),
+            ("r", "Quarantine room media"),
+            ("u", "Quarantine user media"),
+            ("c", "Purge remote cache"),
+            ("o", "Delete old local media"),
+            ("s", "Usage statistics"),
+        ]
+    }
+}
+
+impl MediaView {
+    fn confirm_purge_cache(&mut self, before_ts: u64) {
+        self.prompt.clear();
+        self.prompt.msg = format!(
+            "Purge the cached remote media not accessed since {} ({}) ?\n\
+             They will be fetched again from their server when needed",
+            format_ts(before_ts),
+            format_age(before_ts, now_ms())
+        );
+        self.prompt.true_button.push_str("Purge");
+        self.prompt.false_button.push_str("Cancel");
+        self.cur_prompt = CurPrompt::ConfirmPurgeCache(before_ts);
+    }
+
+    fn old_media_query(&self) -> Result<OldMediaQuery, String> {
+        Ok(OldMediaQuery {
+            before_ts: parse_time(self.prompt.fields[0].1.as_str(), now_ms())?,
+            size_gt: parse_size(self.prompt.fields[1].1.as_str())?,
+            keep_profiles: self.prompt.fields[2].1.as_str() == "true",
+        })
+    }
+
+    /// Asks for confirmation along with an estimate of what will be deleted
+    fn confirm_delete_old(&mut self, query: OldMediaQuery, synapse: &mut Synapse) {
+        // Statistics only know the upload date, it is an upper bound
+        let estimate = synapse.total_media_usage(&MediaStatsQuery {
+            until_ts: Some(query.before_ts),
+            ..Default::default()
+        });
+        self.prompt.clear();
+        self.prompt.msg = format!(
+            "Delete the local media not accessed since {} ({}){}{} ?",
+            format_ts(query.before_ts),
+            format_age(query.before_ts, now_ms()),
+            if query.size_gt > 0 {
+                format!(", larger than {}", format_bytes(query.size_gt))
+            } else {
+                String::new()
+            },
+            if query.keep_profiles {
+                ", keeping profile pictures"
+            } else {
+                ""
+            }
+        );
+        match estimate {
+            Ok((count, bytes)) => self.prompt.msg.push_str(&format!(
+                "\nAt most {} media ({}) were uploaded before that date",
+                count,
+                format_bytes(bytes)
+            )),
+            Err(e) => self
+                .prompt
+                .msg
+                .push_str(&format!("\nNo estimate available : {}", e)),
+        }
+        self.prompt.error.push_str("This cannot be undone");
+        self.prompt.true_button.push_str("Delete");
+        self.prompt.false_button.push_str("Cancel");
+        self.cur_prompt = CurPrompt::ConfirmDeleteOld(query);
+    }
+}
@@ -0,0 +1,337 @@
+use crossterm::event::{Event, KeyCode};
+use tui::{
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Style},
+    text::Span,
+    widgets::{BarChart, Block, Borders, Paragraph},
+};
+
+use crate::{
+    backend::{MediaStatsQuery, UserMediaStatV1},
+    common::{
+        editable::{Editable, EditableWidget},
+        human::{format_bytes, format_ts, now_ms, parse_time},
+        prompt::Prompt,
+        table::EditTable,
+        HandleRes, ViewImpl,
+    },
+    state::State,
+};
+
+#[derive(Default)]
+enum CurPrompt {
+    #[default]
+    None,
+    Notice,
+    Filter,
+}
+
+const STATS_COLUMNS: [&str; 4] = ["User", "Name", "Media", "Size"];
+const PAGE_SIZE: usize = 100;
+/// Server side orderings, cycled with [o]
+const ORDERS: [(&str, bool, &str); 3] = [
+    ("media_length", true, "largest first"),
+    ("media_count", true, "most media first"),
+    ("user_id", false, "by user id"),
+];
+const DEFAULT_TOP: usize = 10;
+
+/// Ranks the local users by media usage
+pub struct MediaStatsView {
+    cur_prompt: CurPrompt,
+    prompt: Prompt,
+    query: MediaStatsQuery,
+    order: usize,
+    table: EditTable,
+    stats: Vec<UserMediaStatV1>,
+    /// Number of users matching the query
+    total: usize,
+    /// Number of users in the bar chart
+    top: usize,
+}
+
+impl Default for MediaStatsView {
+    fn default() -> Self {
+        Self {
+            cur_prompt: CurPrompt::default(),
+            prompt: Prompt::default(),
+            query: MediaStatsQuery {
+                order_by: ORDERS[0].0,
+                descending: ORDERS[0].1,
+                ..Default::default()
+            },
+            order: 0,
+            table: EditTable::new(&STATS_COLUMNS),
+            stats: Vec::new(),
+            total: 0,
+            top: DEFAULT_TOP,
+        }
+    }
+}
+
+impl MediaStatsView {
+    fn reload(&mut self, state: &mut State) {
+        self.table.clear();
+        self.stats.clear();
+        self.total = 0;
+        self.load_next_chunk(state);
+    }
+
+    fn load_next_chunk(&mut self, state: &mut State) {
+        if !self.stats.is_empty() && self.stats.len() >= self.total {
+            return;
+        }
+        let page = match state
+            .backend
+            .user_media_stats(self.stats.len(), PAGE_SIZE, &self.query)
+        {
+            Ok(p) => p,
+            Err(e) => {
+                self.prompt.error_notice(e.as_str());
+                self.cur_prompt = CurPrompt::Notice;
+                return;
+            }
+        };
+        self.total = page.total;
+        for u in page.users {
+            self.table.push(vec![
+                Editable::ro_string(u.user_id.as_str()),
+                Editable::ro_string(u.displayname.as_deref().unwrap_or("")),
+                Editable::ro_string(u.media_count.to_string().as_str()),
+                Editable::ro_string(format_bytes(u.media_length).as_str()),
+            ]);
+            self.stats.push(u);
+        }
+    }
+
+    /// Whether the chart shows media counts rather than sizes
+    fn chart_counts(&self) -> bool {
+        self.query.order_by == "media_count"
+    }
+
+    fn status_line(&self) -> String {
+        let mut r = format!(
+            "{}/{} users | {}",
+            self.stats.len(),
+            self.total,
+            ORDERS[self.order].2
+        );
+        if !self.query.search_term.is_empty() {
+            r.push_str(&format!(" | contains '{}'", self.query.search_term));
+        }
+        if let Some(ts) = self.query.from_ts {
+            r.push_str(&format!(" | uploaded after {}", format_ts(ts)));
+        }
+        if let Some(ts) = self.query.until_ts {
+            r.push_str(&format!(" | uploaded before {}", format_ts(ts)));
+        }
+        r
+    }
+
+    fn apply_filter(&mut self) -> Result<(), String> {
+        let opt_time = |s: &str| -> Result<Option<u64>, String> {
+            let s = s.trim();
+            if s.is_empty() {
+                Ok(None)
+            } else {
+                parse_time(s, now_ms()).map(Some)
+            }
+        };
+        let fields = &self.prompt.fields;
+        let top = fields[3]
+            .1
+            .as_str()
+            .trim()
+            .parse::<usize>()
+            .map_err(|_| "The number of users in the chart must be a number")?;
+        self.query.search_term = fields[0].1.as_str().trim().to_string();
+        self.query.from_ts = opt_time(fields[1].1.as_str())?;
+        self.query.until_ts = opt_time(fields[2].1.as_str())?;
+        self.top = top;
+        Ok(())
+    }
+}
+
+impl ViewImpl<State> for MediaStatsView {
+    fn title(&self) -> &'static str {
+        "Media statistics"
+    }
+
+    fn enter_view(&mut self, state: &mut State) {
+        self.reload(state);
+    }
+
+    fn draw_view(
+        &mut self,
+        frame: &mut tui::Frame<tui::backend::CrosstermBackend<&mut std::io::Stdout>>,
+        rect: tui::layout::Rect,
+        _state: &mut State,
+    ) {
+        if !matches!(self.cur_prompt, CurPrompt::None) {
+            self.prompt.draw_view(frame, rect, &mut ());
+            return;
+        }
+
+        let chart_height = if self.top > 0 { rect.height / 2 } else { 0 };
+        let rects = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(1),
+                Constraint::Length(chart_height),
+                Constraint::Min(0),
+            ])
+            .split(rect);
+        frame.render_widget(
+            Paragraph::new(Span::styled(
+                self.status_line(),
+                Style::default().fg(Color::Yellow),
+            )),
+            rects[0],
+        );
+
+        if self.top > 0 {
+            let counts = self.chart_counts();
+            let bars: Vec<(String, u64)> = self
+                .stats
+                .iter()
+                .take(self.top)
+                .map(|u| {
+                    // Only keep the localpart, the server is the same for everyone
+                    let name = u.user_id.trim_start_matches('@');
+                    let name = name.split(':').next().unwrap_or(name);
+                    let value = if counts {
+                        u.media_count
+                    } else {
+                        u.media_length >> 20
+                    };
+                    (name.to_string(), value)
+                })
+                .collect();
+            let data: Vec<(&str, u64)> = bars.iter().map(|(n, v)| (n.as_str(), *v)).collect();
+            let bar_width = if bars.is_empty() {
+                1
+            } else {
+                (rects[1].width.saturating_sub(2) / bars.len() as u16)
+                    .saturating_sub(1)
+                    .max(1)
+            };
+            let title = format!(
+                "Top {} ({})",
+                self.top,
+                if counts { "media" } else { "MiB" }
+            );
+            frame.render_widget(
+                BarChart::default()
+                    .block(Block::default().borders(Borders::ALL).title(title))
+                    .data(&data)
+                    .bar_width(bar_width)
+                    .bar_style(Style::default().fg(Color::Cyan))
+                    .value_style(Style::default().fg(Color::Black).bg(Color::Cyan)),
+                rects[1],
+            );
+        }
+
+        self.table.draw(
+            frame,
+            rects[2],
+            Block::default().borders(Borders::LEFT | Borders::RIGHT),
+        );
+    }
+
+    fn handle_event(&mut self, event: &Event, state: &mut State) -> HandleRes {
+        match self.cur_prompt {
+            CurPrompt::Notice => {
+                let r = self.prompt.handle_event(event, &mut ());
+                if let HandleRes::Exit(_) = r {
+                    self.cur_prompt = CurPrompt::None;
+                    return HandleRes::ReDraw;
+                }
+                return r;
+            }
+            CurPrompt::Filter => {
+                let r = self.prompt.handle_event(event, &mut ());
+                if let HandleRes::Exit(apply) = r {
+                    if apply {
+                        if let Err(e) = self.apply_filter() {
+                            self.prompt.error = e;
+                            return HandleRes::ReDraw;
+                        }
+                        self.cur_prompt = CurPrompt::None;
+                        self.reload(state);
+                    } else {
+                        self.cur_prompt = CurPrompt::None;
+                    }
+                    return HandleRes::ReDraw;
+                }
+                return r;
+            }
+            CurPrompt::None => {}
+        }
+
+        let key = match event {
+            Event::Key(k) => k,
+            _ => return HandleRes::Ignored,
+        };
+
+        let r = self.table.handle_event(key);
+        if !matches!(r, HandleRes::Ignored) {
+            // Fetch more users when reaching the end of the list
+            if self.table.focus_y + 1 >= self.table.len() {
+                self.load_next_chunk(state);
+            }
+            return r;
+        }
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => HandleRes::Exit(false),
+            KeyCode::F(5) => {
+                self.reload(state);
+                HandleRes::ReDraw
+            }
+            KeyCode::Char('o') => {
+                self.order = (self.order + 1) % ORDERS.len();
+                self.query.order_by = ORDERS[self.order].0;
+                self.query.descending = ORDERS[self.order].1;
+                self.reload(state);
+                HandleRes::ReDraw
+            }
+            KeyCode::Char('/') => {
+                let opt_ts = |ts: Option<u64>| ts.map(format_ts).unwrap_or_default();
+                self.prompt.clear();
+                self.prompt.msg.push_str(
+                    "Filter the statistics\nDates as YYYY-MM-DD [HH:MM] (UTC) or ages like 30d",
+                );
+                self.prompt.fields.push((
+                    "User contains".into(),
+                    Editable::string(self.query.search_term.as_str()),
+                ));
+                self.prompt.fields.push((
+                    "Uploaded after".into(),
+                    Editable::string(opt_ts(self.query.from_ts).as_str()),
+                ));
+                self.prompt.fields.push((
+                    "Uploaded before".into(),
+                    Editable::string(opt_ts(self.query.until_ts).as_str()),
+                ));
+                self.prompt.fields.push((
+                    "Users in the chart".into(),
+                    Editable::string(self.top.to_string().as_str()),
+                ));
+                self.prompt.true_button.push_str("Apply");
+                self.prompt.false_button.push_str("Cancel");
+                self.cur_prompt = CurPrompt::Filter;
+                HandleRes::ReDraw
+            }
+            _ => HandleRes::Ignored,
+        }
+    }
+
+    fn hints(&self) -> Vec<(&'static str, &'static str)> {
+        vec![
+            ("/", "Filter"),
+            ("o", "Change order"),
+            ("F5", "Reload"),
+            ("Esc", "Back"),
+        ]
+    }
+}
@@ -2,3 +2,39 @@ mod home;
 pub use home::*;
 mod users;
 pub use users::*;
+mod user_import;
+pub use user_import::*;
+mod user_devices;
+pub use user_devices::*;
+mod user_whois;
+pub use user_whois::*;
+mod user_media;
+pub use user_media::*;
+mod user_rooms;
+pub use user_rooms::*;
+mod rooms;
+pub use rooms::*;
+mod room_aliases;
+pub use room_aliases::*;
+mod room_messages;
+pub use room_messages::*;
+mod room_directory;
+pub use room_directory::*;
+mod room_detail;
+pub use room_detail::*;
+mod event_context;
+pub use event_context::*;
+mod media;
+pub use media::*;
+mod media_stats;
+pub use media_stats::*;
+mod reports;
+pub use reports::*;
+mod registration_tokens;
+pub use registration_tokens::*;
+mod server_notice;
+pub use server_notice::*;
+mod federation;
+pub use federation::*;
+mod destination;
+pub use destination::*;
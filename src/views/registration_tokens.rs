@@ -0,0 +1,326 @@
+use crossterm::event::{Event, KeyCode};
+use tui::{
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Style},
+    text::Span,
+    widgets::{Block, Borders, Paragraph},
+};
+
+use crate::{
+    backend::{NewRegistrationTokenV1, RegistrationTokenUpdateV1, Synapse},
+    common::{
+        editable::{Editable, EditableWidget},
+        human::format_ts,
+        prompt::Prompt,
+        table::EditTable,
+        HandleRes, ViewImpl,
+    },
+    state::State,
+};
+
+#[derive(Default)]
+enum CurPrompt {
+    #[default]
+    None,
+    Notice,
+    Create,
+    ConfirmDelete,
+}
+
+const TOKEN_COLUMNS: [&str; 5] = ["Token", "Uses allowed", "Pending", "Completed", "Expires"];
+const USES_COLUMN: usize = 1;
+const EXPIRY_COLUMN: usize = 4;
+/// Length of the tokens generated by the server
+const DEFAULT_LENGTH: u64 = 16;
+
+/// Manages the tokens required to register on invite-only servers
+pub struct RegistrationTokensView {
+    cur_prompt: CurPrompt,
+    prompt: Prompt,
+    table: EditTable,
+    /// Only list the valid (or invalid) tokens
+    valid: Option<bool>,
+    /// Rows to delete once confirmed
+    to_delete: Vec<usize>,
+}
+
+impl Default for RegistrationTokensView {
+    fn default() -> Self {
+        Self {
+            cur_prompt: CurPrompt::default(),
+            prompt: Prompt::default(),
+            table: EditTable::new(&TOKEN_COLUMNS).with_selection(),
+            valid: None,
+            to_delete: Vec::new(),
+        }
+    }
+}
+
+impl RegistrationTokensView {
+    fn reload(&mut self, synapse: &mut Synapse) {
+        self.table.clear();
+        let tokens = match synapse.list_registration_tokens(self.valid) {
+            Ok(t) => t,
+            Err(e) => {
+                self.prompt.error_notice(e.as_str());
+                self.cur_prompt = CurPrompt::Notice;
+                return;
+            }
+        };
+        for t in tokens {
+            self.table.push(vec![
+                Editable::ro_string(t.token.as_str()),
+                Editable::num(t.uses_allowed),
+                Editable::ro_string(t.pending.to_string().as_str()),
+                Editable::ro_string(t.completed.to_string().as_str()),
+                Editable::date(t.expiry_time),
+            ]);
+        }
+    }
+
+    /// Applies a value the user just modified in the table
+    fn apply_edit(&mut self, x: usize, y: usize, synapse: &mut Synapse) {
+        let token = self.table.rows[y][0].as_str().to_string();
+        let cell = &mut self.table.rows[y][x];
+        let changes = match x {
+            USES_COLUMN => cell.as_num().map(|n| RegistrationTokenUpdateV1 {
+                uses_allowed: Some(n),
+                ..Default::default()
+            }),
+            EXPIRY_COLUMN => cell.as_ts().map(|ts| RegistrationTokenUpdateV1 {
+                expiry_time: Some(ts),
+                ..Default::default()
+            }),
+            _ => return,
+        };
+        let changes = match changes {
+            Ok(c) => c,
+            Err(e) => {
+                cell.restore_orig();
+                self.prompt.error_notice(e.as_str());
+                self.cur_prompt = CurPrompt::Notice;
+                return;
+            }
+        };
+        match synapse.update_registration_token(token.as_str(), &changes) {
+            Ok(_) => {
+                cell.forget_orig();
+                // Show the date as the server understood it
+                if let Some(Some(ts)) = changes.expiry_time {
+                    *cell = Editable::date(Some(ts));
+                }
+            }
+            Err(e) => {
+                cell.restore_orig();
+                self.prompt.error_notice(e.as_str());
+                self.cur_prompt = CurPrompt::Notice;
+            }
+        }
+    }
+
+    fn create(&mut self, synapse: &mut Synapse) -> Result<(), String> {
+        let fields = &self.prompt.fields;
+        let token = fields[0].1.as_str().trim();
+        let new = NewRegistrationTokenV1 {
+            token: if token.is_empty() {
+                None
+            } else {
+                Some(token.to_string())
+            },
+            length: if token.is_empty() {
+                fields[1].1.as_num()?
+            } else {
+                None
+            },
+            uses_allowed: fields[2].1.as_num()?,
+            expiry_time: fields[3].1.as_ts()?,
+        };
+        let created = synapse.create_registration_token(&new)?;
+        self.reload(synapse);
+        let expiry = created
+            .expiry_time
+            .map(|ts| format!(", expires {}", format_ts(ts)))
+            .unwrap_or_default();
+        self.prompt
+            .notice(&format!("Created token {}{}", created.token, expiry));
+        self.cur_prompt = CurPrompt::Notice;
+        Ok(())
+    }
+
+    fn delete_tokens(&mut self, synapse: &mut Synapse) {
+        let mut errors = String::new();
+        for y in self.to_delete.drain(..) {
+            let token = self.table.rows[y][0].as_str();
+            if let Err(e) = synapse.delete_registration_token(token) {
+                errors.push_str(&format!("{} : {}\n", token, e));
+            }
+        }
+        self.reload(synapse);
+        if !errors.is_empty() {
+            self.prompt.error_notice(errors.as_str());
+            self.cur_prompt = CurPrompt::Notice;
+        }
+    }
+}
+
+impl ViewImpl<State> for RegistrationTokensView {
+    fn title(&self) -> &'static str {
+        "Tokens"
+    }
+
+    fn enter_view(&mut self, state: &mut State) {
+        self.reload(&mut state.backend);
+    }
+
+    fn draw_view(
+        &mut self,
+        frame: &mut tui::Frame<tui::backend::CrosstermBackend<&mut std::io::Stdout>>,
+        rect: tui::layout::Rect,
+        _state: &mut State,
+    ) {
+        if !matches!(self.cur_prompt, CurPrompt::None) {
+            self.prompt.draw_view(frame, rect, &mut ());
+            return;
+        }
+        let rects = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(0)])
+            .split(rect);
+        let status = format!(
+            "{} {}tokens, {} selected | Uses allowed empty for unlimited, expiry as YYYY-MM-DD [HH:MM] or +7d",
+            self.table.len(),
+            match self.valid {
+                Some(true) => "valid ",
+                Some(false) => "invalid ",
+                None => "",
+            },
+            self.table.num_selected()
+        );
+        frame.render_widget(
+            Paragraph::new(Span::styled(status, Style::default().fg(Color::Yellow))),
+            rects[0],
+        );
+        self.table.draw(
+            frame,
+            rects[1],
+            Block::default().borders(Borders::LEFT | Borders::RIGHT),
+        );
+    }
+
+    fn handle_event(&mut self, event: &Event, state: &mut State) -> HandleRes {
+        match self.cur_prompt {
+            CurPrompt::Notice => {
+                let r = self.prompt.handle_event(event, &mut ());
+                if let HandleRes::Exit(_) = r {
+                    self.cur_prompt = CurPrompt::None;
+                    return HandleRes::ReDraw;
+                }
+                return r;
+            }
+            CurPrompt::Create => {
+                let r = self.prompt.handle_event(event, &mut ());
+                if let HandleRes::Exit(confirmed) = r {
+                    if !confirmed {
+                        self.cur_prompt = CurPrompt::None;
+                    } else if let Err(e) = self.create(&mut state.backend) {
+                        self.prompt.error = e;
+                    }
+                    return HandleRes::ReDraw;
+                }
+                return r;
+            }
+            CurPrompt::ConfirmDelete => {
+                let r = self.prompt.handle_event(event, &mut ());
+                if let HandleRes::Exit(confirmed) = r {
+                    self.cur_prompt = CurPrompt::None;
+                    if confirmed {
+                        self.delete_tokens(&mut state.backend);
+                    }
+                    return HandleRes::ReDraw;
+                }
+                return r;
+            }
+            CurPrompt::None => {}
+        }
+
+        let key = match event {
+            Event::Key(k) => k,
+            _ => return HandleRes::Ignored,
+        };
+
+        let r = self.table.handle_event(key);
+        if let Some((x, y)) = self.table.take_committed() {
+            self.apply_edit(x, y, &mut state.backend);
+        }
+        if !matches!(r, HandleRes::Ignored) {
+            return r;
+        }
+
+        match key.code {
+            KeyCode::F(5) => {
+                self.reload(&mut state.backend);
+                HandleRes::ReDraw
+            }
+            KeyCode::Char('v') => {
+                self.valid = match self.valid {
+                    None => Some(true),
+                    Some(true) => Some(false),
+                    Some(false) => None,
+                };
+                self.reload(&mut state.backend);
+                HandleRes::ReDraw
+            }
+            KeyCode::Char('n') => {
+                self.prompt.clear();
+                self.prompt.msg.push_str(
+                    "New registration token\nLeave the token empty to generate one\nEmpty uses/expiry for no limit",
+                );
+                self.prompt
+                    .fields
+                    .push(("Token".into(), Editable::string("")));
+                self.prompt.fields.push((
+                    "Generated length".into(),
+                    Editable::num(Some(DEFAULT_LENGTH)),
+                ));
+                self.prompt
+                    .fields
+                    .push(("Uses allowed".into(), Editable::num(None)));
+                self.prompt
+                    .fields
+                    .push(("Expires (+7d, date)".into(), Editable::date(None)));
+                self.prompt.true_button.push_str("Create");
+                self.prompt.false_button.push_str("Cancel");
+                self.cur_prompt = CurPrompt::Create;
+                HandleRes::ReDraw
+            }
+            KeyCode::Delete | KeyCode::Char('d') => {
+                self.to_delete = self.table.selection();
+                if self.to_delete.is_empty() {
+                    return HandleRes::Handled;
+                }
+                self.prompt.clear();
+                self.prompt.msg = format!("Delete {} token(s) ?", self.to_delete.len());
+                for y in self.to_delete.iter() {
+                    self.prompt.msg.push_str("\n  ");
+                    self.prompt.msg.push_str(self.table.rows[*y][0].as_str());
+                }
+                self.prompt.true_button.push_str("Delete");
+                self.prompt.false_button.push_str("Cancel");
+                self.cur_prompt = CurPrompt::ConfirmDelete;
+                HandleRes::ReDraw
+            }
+            _ => HandleRes::Ignored,
+        }
+    }
+
+    fn hints(&self) -> Vec<(&'static str, &'static str)> {
+        vec![
+            ("Enter", "Edit"),
+            ("Space", "Select"),
+            ("n", "New token"),
+            ("d/Del", "Delete"),
+            ("v", "All/Valid/Invalid"),
+            ("F5", "Reload"),
+        ]
+    }
+}
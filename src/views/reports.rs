@@ -0,0 +1,415 @@
+use crossterm::event::{Event, KeyCode};
+use tui::{
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph},
+};
+
+use crate::{
+    backend::{EventReportV1, Synapse},
+    common::{
+        editable::{Editable, EditableWidget},
+        human::format_ts,
+        prompt::Prompt,
+        table::EditTable,
+        Goto, HandleRes, ViewImpl,
+    },
+    state::State,
+    views::EventContextView,
+};
+
+/// Moderation actions on the focused report
+#[derive(Clone, Copy)]
+enum ReportAction {
+    Dismiss,
+    Redact,
+    ShadowBan,
+}
+impl ReportAction {
+    fn from_key(key: KeyCode) -> Option<Self> {
+        Some(match key {
+            KeyCode::Char('d') | KeyCode::Delete => Self::Dismiss,
+            KeyCode::Char('x') => Self::Redact,
+            KeyCode::Char('s') => Self::ShadowBan,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Default)]
+enum CurPrompt {
+    #[default]
+    None,
+    Notice,
+    Confirm(ReportAction),
+}
+
+const REPORT_COLUMNS: [&str; 7] = [
+    "ID", "Received", "Score", "Reason", "Reporter", "Sender", "Room",
+];
+const PAGE_SIZE: usize = 50;
+
+pub struct ReportsView {
+    cur_prompt: CurPrompt,
+    prompt: Prompt,
+    table: EditTable,
+    reports: Vec<EventReportV1>,
+    /// Number of reports on the server
+    total: usize,
+    /// Report shown with its event instead of the list
+    detail: Option<EventReportV1>,
+    scroll: u16,
+    /// View opened on top of the reports
+    sub_view: Option<Box<dyn ViewImpl<State>>>,
+}
+
+impl Default for ReportsView {
+    fn default() -> Self {
+        Self {
+            cur_prompt: CurPrompt::default(),
+            prompt: Prompt::default(),
+            table: EditTable::new(&REPORT_COLUMNS),
+            reports: Vec::new(),
+            total: 0,
+            detail: None,
+            scroll: 0,
+            sub_view: None,
+        }
+    }
+}
+
+impl ReportsView {
+    fn reload(&mut self, synapse: &mut Synapse) {
+        self.table.clear();
+        self.reports.clear();
+        self.total = 0;
+        self.load_next_chunk(synapse);
+    }
+
+    fn load_next_chunk(&mut self, synapse: &mut Synapse) {
+        if !self.reports.is_empty() && self.reports.len() >= self.total {
+            return;
+        }
+        let page = match synapse.list_event_reports(self.reports.len(), PAGE_SIZE) {
+            Ok(p) => p,
+            Err(e) => {
+                self.prompt.error_notice(e.as_str());
+                self.cur_prompt = CurPrompt::Notice;
+                return;
+            }
+        };
+        self.total = page.total;
+        for r in page.event_reports {
+            self.table.push(vec![
+                Editable::ro_string(r.id.to_string().as_str()),
+                Editable::ro_string(format_ts(r.received_ts).as_str()),
+                Editable::ro_string(r.score.map(|s| s.to_string()).unwrap_or_default().as_str()),
+                Editable::ro_string(r.reason.as_deref().unwrap_or("")),
+                Editable::ro_string(r.user_id.as_str()),
+                Editable::ro_string(r.sender.as_str()),
+                Editable::ro_string(r.name.as_deref().unwrap_or(r.room_id.as_str())),
+            ]);
+            self.reports.push(r);
+        }
+    }
+
+    /// The report shown in the detail pane or focused in the list
+    fn cur_report(&self) -> Option<&EventReportV1> {
+        match self.detail.as_ref() {
+            Some(d) => Some(d),
+            None => self.table.cur_row().map(|y| &self.reports[y]),
+        }
+    }
+
+    fn open_detail(&mut self, synapse: &mut Synapse) {
+        let id = match self.cur_report() {
+            Some(r) => r.id,
+            None => return,
+        };
+        match synapse.event_report(id) {
+            Ok(r) => {
+                self.detail = Some(r);
+                self.scroll = 0;
+            }
+            Err(e) => {
+                self.prompt.error_notice(e.as_str());
+                self.cur_prompt = CurPrompt::Notice;
+            }
+        }
+    }
+
+    fn confirm(&mut self, action: ReportAction) {
+        let r = match self.cur_report() {
+            Some(r) => r,
+            None => return,
+        };
+        let mut prompt = Prompt::default();
+        match action {
+            ReportAction::Dismiss => {
+                prompt.msg = format!("Dismiss report {} ? The reported event is kept", r.id);
+                prompt.true_button.push_str("Dismiss");
+            }
+            ReportAction::Redact => {
+                prompt.msg = format!(
+                    "Redact {} sent by {} ?\nYour account must be in the room with enough power",
+                    r.event_id, r.sender
+                );
+                prompt.fields.push(("Reason".into(), Editable::string("")));
+                prompt
+                    .fields
+                    .push(("Dismiss the report".into(), Editable::bool(true)));
+                prompt.true_button.push_str("Redact");
+            }
+            ReportAction::ShadowBan => {
+                prompt.msg = format!("Shadow-ban {} ?", r.sender);
+                prompt
+                    .fields
+                    .push(("Dismiss the report".into(), Editable::bool(true)));
+                prompt.true_button.push_str("Shadow-ban");
+            }
+        }
+        prompt.false_button.push_str("Cancel");
+        self.prompt = prompt;
+        self.cur_prompt = CurPrompt::Confirm(action);
+    }
+
+    fn apply(&mut self, action: ReportAction, synapse: &mut Synapse) -> Result<(), String> {
+        let (id, room_id, event_id, sender) = match self.cur_report() {
+            Some(r) => (
+                r.id,
+                r.room_id.clone(),
+                r.event_id.clone(),
+                r.sender.clone(),
+            ),
+            None => return Ok(()),
+        };
+        let dismiss = match action {
+            ReportAction::Dismiss => true,
+            ReportAction::Redact => {
+                let reason = self.prompt.fields[0].1.as_str().trim().to_string();
+                synapse.redact_event(room_id.as_str(), event_id.as_str(), reason.as_str())?;
+                self.prompt.fields[1].1.as_str() == "true"
+            }
+            ReportAction::ShadowBan => {
+                synapse.set_shadow_banned(sender.as_str(), true)?;
+                self.prompt.fields[0].1.as_str() == "true"
+            }
+        };
+        if dismiss {
+            synapse.delete_event_report(id)?;
+            self.detail = None;
+            if let Some(y) = self.reports.iter().position(|r| r.id == id) {
+                self.reports.remove(y);
+                self.table.remove(y);
+                self.total = self.total.saturating_sub(1);
+            }
+        }
+        Ok(())
+    }
+
+    fn draw_detail(
+        &self,
+        frame: &mut tui::Frame<tui::backend::CrosstermBackend<&mut std::io::Stdout>>,
+        rect: tui::layout::Rect,
+        r: &EventReportV1,
+    ) {
+        let gray = Style::default().fg(Color::DarkGray);
+        let field = |name: &str, value: String| {
+            Spans::from(vec![
+                Span::styled(format!("{:>10} : ", name), gray),
+                Span::raw(value),
+            ])
+        };
+        let mut lines = vec![
+            field("Received", format_ts(r.received_ts)),
+            field("Reporter", r.user_id.clone()),
+            field("Sender", r.sender.clone()),
+            field(
+                "Room",
+                format!("{} {}", r.room_id, r.name.as_deref().unwrap_or("")),
+            ),
+            field("Score", r.score.map(|s| s.to_string()).unwrap_or_default()),
+            field("Reason", r.reason.clone().unwrap_or_default()),
+            Spans::from(""),
+        ];
+        let json = serde_json::to_string_pretty(&r.event_json).unwrap_or_default();
+        lines.extend(json.lines().map(|l| Spans::from(l.to_string())));
+        frame.render_widget(
+            Paragraph::new(lines).scroll((self.scroll, 0)).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!("Report {} : {}", r.id, r.event_id)),
+            ),
+            rect,
+        );
+    }
+}
+
+impl ViewImpl<State> for ReportsView {
+    fn title(&self) -> &'static str {
+        "Reports"
+    }
+
+    fn enter_view(&mut self, state: &mut State) {
+        self.load_next_chunk(&mut state.backend);
+    }
+
+    fn draw_view(
+        &mut self,
+        frame: &mut tui::Frame<tui::backend::CrosstermBackend<&mut std::io::Stdout>>,
+        rect: tui::layout::Rect,
+        state: &mut State,
+    ) {
+        if let Some(v) = self.sub_view.as_mut() {
+            v.draw_view(frame, rect, state);
+            return;
+        }
+        if !matches!(self.cur_prompt, CurPrompt::None) {
+            self.prompt.draw_view(frame, rect, &mut ());
+            return;
+        }
+        if let Some(r) = self.detail.as_ref() {
+            self.draw_detail(frame, rect, r);
+            return;
+        }
+
+        let rects = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(0)])
+            .split(rect);
+        frame.render_widget(
+            Paragraph::new(Span::styled(
+                format!("{}/{} reports", self.reports.len(), self.total),
+                Style::default().fg(Color::Yellow),
+            )),
+            rects[0],
+        );
+        self.table.draw(
+            frame,
+            rects[1],
+            Block::default().borders(Borders::LEFT | Borders::RIGHT),
+        );
+    }
+
+    fn handle_event(&mut self, event: &Event, state: &mut State) -> HandleRes {
+        if let Some(v) = self.sub_view.as_mut() {
+            let r = v.handle_event(event, state);
+            if let HandleRes::Exit(_) = r {
+                self.sub_view = None;
+                return HandleRes::ReDraw;
+            }
+            return r;
+        }
+        match self.cur_prompt {
+            CurPrompt::Notice => {
+                let r = self.prompt.handle_event(event, &mut ());
+                if let HandleRes::Exit(_) = r {
+                    self.cur_prompt = CurPrompt::None;
+                    return HandleRes::ReDraw;
+                }
+                return r;
+            }
+            CurPrompt::Confirm(action) => {
+                let r = self.prompt.handle_event(event, &mut ());
+                if let HandleRes::Exit(confirmed) = r {
+                    self.cur_prompt = CurPrompt::None;
+                    if confirmed {
+                        if let Err(e) = self.apply(action, &mut state.backend) {
+                            self.prompt.error_notice(e.as_str());
+                            self.cur_prompt = CurPrompt::Notice;
+                        }
+                    }
+                    return HandleRes::ReDraw;
+                }
+                return r;
+            }
+            CurPrompt::None => {}
+        }
+
+        let key = match event {
+            Event::Key(k) => k,
+            _ => return HandleRes::Ignored,
+        };
+
+        if let Some(action) = ReportAction::from_key(key.code) {
+            self.confirm(action);
+            return HandleRes::ReDraw;
+        }
+        if let KeyCode::Char('c') = key.code {
+            if let Some(r) = self.cur_report() {
+                let mut view = EventContextView::new(r.room_id.as_str(), r.event_id.as_str());
+                view.enter_view(state);
+                self.sub_view = Some(Box::new(view));
+            }
+            return HandleRes::ReDraw;
+        }
+        if let KeyCode::Char('g') = key.code {
+            if let Some(r) = self.cur_report() {
+                state.goto(Goto::Room(r.room_id.clone()));
+            }
+            return HandleRes::ReDraw;
+        }
+
+        if self.detail.is_some() {
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') => self.detail = None,
+                KeyCode::Up => self.scroll = self.scroll.saturating_sub(1),
+                KeyCode::Down => self.scroll = self.scroll.saturating_add(1),
+                KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(10),
+                KeyCode::PageDown => self.scroll = self.scroll.saturating_add(10),
+                KeyCode::Home => self.scroll = 0,
+                _ => return HandleRes::Ignored,
+            }
+            return HandleRes::ReDraw;
+        }
+
+        if let KeyCode::Enter = key.code {
+            self.open_detail(&mut state.backend);
+            return HandleRes::ReDraw;
+        }
+
+        let r = self.table.handle_event(key);
+        if !matches!(r, HandleRes::Ignored) {
+            // Fetch more reports when reaching the end of the list
+            if self.table.focus_y + 1 >= self.table.len() {
+                self.load_next_chunk(&mut state.backend);
+            }
+            return r;
+        }
+
+        match key.code {
+            KeyCode::F(5) => {
+                self.reload(&mut state.backend);
+                HandleRes::ReDraw
+            }
+            _ => HandleRes::Ignored,
+        }
+    }
+
+    fn tick(&mut self, state: &mut State) -> HandleRes {
+        if let Some(v) = self.sub_view.as_mut() {
+            return v.tick(state);
+        }
+        HandleRes::Ignored
+    }
+
+    fn hints(&self) -> Vec<(&'static str, &'static str)> {
+        if let Some(v) = self.sub_view.as_ref() {
+            return v.hints();
+        }
+        let mut hints = if self.detail.is_some() {
+            vec![("↑/↓", "Scroll"), ("Esc", "Back to the list")]
+        } else {
+            vec![("Enter", "Show event"), ("F5", "Reload")]
+        };
+        hints.extend([
+            ("d/Del", "Dismiss"),
+            ("x", "Redact event"),
+            ("c", "Event context"),
+            ("s", "Shadow-ban sender"),
+            ("g", "Go to room"),
+        ]);
+        hints
+    }
+}
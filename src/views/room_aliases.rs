@@ -0,0 +1,350 @@
+use crossterm::event::{Event, KeyCode};
+use tui::{
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Style},
+    text::Span,
+    widgets::{Block, Borders, Paragraph},
+};
+
+use crate::{
+    backend::Synapse,
+    common::{
+        editable::{Editable, EditableWidget},
+        prompt::Prompt,
+        table::EditTable,
+        Goto, HandleRes, ViewImpl,
+    },
+    state::State,
+};
+
+#[derive(Default)]
+enum CurPrompt {
+    #[default]
+    None,
+    Notice,
+    Create,
+    ConfirmDelete(String),
+    Resolve,
+    /// Result of a lookup, offering to go to the room
+    Resolved(String),
+}
+
+const ALIAS_COLUMNS: [&str; 3] = ["Alias", "Points to", "Canonical"];
+
+/// Local aliases of a room and where each of them points to
+pub struct RoomAliasesView {
+    room_id: String,
+    canonical_alias: Option<String>,
+    cur_prompt: CurPrompt,
+    prompt: Prompt,
+    table: EditTable,
+    /// Whether the canonical alias does not point to the room
+    canonical_broken: bool,
+    /// Result of the last action
+    status: String,
+    /// Whether an alias was created or deleted
+    changed: bool,
+}
+
+impl RoomAliasesView {
+    pub fn new(room_id: &str) -> Self {
+        Self {
+            room_id: room_id.to_string(),
+            canonical_alias: None,
+            cur_prompt: CurPrompt::default(),
+            prompt: Prompt::default(),
+            table: EditTable::new(&ALIAS_COLUMNS),
+            canonical_broken: false,
+            status: String::new(),
+            changed: false,
+        }
+    }
+
+    fn reload(&mut self, synapse: &mut Synapse) {
+        self.table.clear();
+        self.canonical_broken = false;
+        if let Err(e) = self.load(synapse) {
+            self.prompt.error_notice(e.as_str());
+            self.cur_prompt = CurPrompt::Notice;
+        }
+    }
+
+    /// Lists the aliases, plus the canonical alias if it is not one of them,
+    /// and resolves each of them
+    fn load(&mut self, synapse: &mut Synapse) -> Result<(), String> {
+        self.canonical_alias = synapse.room_details(self.room_id.as_str())?.canonical_alias;
+        let mut aliases = synapse.room_aliases(self.room_id.as_str())?;
+        if let Some(c) = self.canonical_alias.as_ref() {
+            if !aliases.contains(c) {
+                aliases.push(c.clone());
+            }
+        }
+        for alias in aliases {
+            let target = match synapse.resolve_room_alias(alias.as_str()) {
+                Ok(Some(a)) if a.room_id == self.room_id => "this room".to_string(),
+                Ok(Some(a)) => a.room_id,
+                Ok(None) => "nothing".to_string(),
+                Err(e) => e,
+            };
+            let canonical = self.canonical_alias.as_ref() == Some(&alias);
+            if canonical && target != "this room" {
+                self.canonical_broken = true;
+            }
+            self.table.push(vec![
+                Editable::ro_string(alias.as_str()),
+                Editable::ro_string(target.as_str()),
+                Editable::ro_bool(canonical),
+            ]);
+        }
+        Ok(())
+    }
+
+    fn cur_alias(&self) -> Option<String> {
+        self.table
+            .cur_row()
+            .map(|y| self.table.rows[y][0].as_str().to_string())
+    }
+
+    fn create_alias(&mut self, state: &mut State) -> Result<(), String> {
+        let alias = self.prompt.fields[0].1.as_str().trim().to_string();
+        if !alias.starts_with('#') || !alias.contains(':') {
+            return Err("An alias looks like #name:server".into());
+        }
+        state
+            .backend
+            .create_room_alias(alias.as_str(), self.room_id.as_str())?;
+        self.status = format!("Created {}", alias);
+        self.changed = true;
+        self.reload(&mut state.backend);
+        Ok(())
+    }
+
+    fn delete_alias(&mut self, alias: &str, state: &mut State) {
+        match state.backend.delete_room_alias(alias) {
+            Ok(_) => {
+                self.status = format!("Deleted {}", alias);
+                self.changed = true;
+                self.reload(&mut state.backend);
+            }
+            Err(e) => {
+                self.prompt.error_notice(e.as_str());
+                self.cur_prompt = CurPrompt::Notice;
+            }
+        }
+    }
+
+    /// Looks up the alias typed in the prompt and shows where it points to
+    fn resolve_alias(&mut self, state: &mut State) {
+        let alias = self.prompt.fields[0].1.as_str().trim().to_string();
+        self.prompt.clear();
+        match state.backend.resolve_room_alias(alias.as_str()) {
+            Ok(Some(a)) => {
+                self.prompt.msg = format!(
+                    "{} points to {}\nKnown by : {}",
+                    alias,
+                    a.room_id,
+                    a.servers.join(", ")
+                );
+                self.prompt.true_button.push_str("Go to room");
+                self.prompt.false_button.push_str("Close");
+                self.cur_prompt = CurPrompt::Resolved(a.room_id);
+            }
+            Ok(None) => {
+                self.prompt
+                    .notice(format!("{} does not exist", alias).as_str());
+                self.cur_prompt = CurPrompt::Notice;
+            }
+            Err(e) => {
+                self.prompt.error_notice(e.as_str());
+                self.cur_prompt = CurPrompt::Notice;
+            }
+        }
+    }
+
+    fn status_line(&self) -> String {
+        let mut r = format!("{} aliases", self.table.len());
+        match self.canonical_alias.as_ref() {
+            Some(c) if self.canonical_broken => {
+                r.push_str(&format!(" | canonical alias {} is broken", c))
+            }
+            Some(c) => r.push_str(&format!(" | canonical alias {}", c)),
+            None => r.push_str(" | no canonical alias"),
+        }
+        if !self.status.is_empty() {
+            r.push_str(" | ");
+            r.push_str(self.status.as_str());
+        }
+        r
+    }
+}
+
+impl ViewImpl<State> for RoomAliasesView {
+    fn title(&self) -> &'static str {
+        "Room aliases"
+    }
+
+    fn enter_view(&mut self, state: &mut State) {
+        self.reload(&mut state.backend);
+    }
+
+    fn draw_view(
+        &mut self,
+        frame: &mut tui::Frame<tui::backend::CrosstermBackend<&mut std::io::Stdout>>,
+        rect: tui::layout::Rect,
+        _state: &mut State,
+    ) {
+        if !matches!(self.cur_prompt, CurPrompt::None) {
+            self.prompt.draw_view(frame, rect, &mut ());
+            return;
+        }
+
+        let rects = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(0)])
+            .split(rect);
+        let color = if self.canonical_broken {
+            Color::Red
+        } else {
+            Color::Yellow
+        };
+        frame.render_widget(
+            Paragraph::new(Span::styled(self.status_line(), Style::default().fg(color))),
+            rects[0],
+        );
+        self.table.draw(
+            frame,
+            rects[1],
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("{} : aliases", self.room_id)),
+        );
+    }
+
+    fn handle_event(&mut self, event: &Event, state: &mut State) -> HandleRes {
+        match self.cur_prompt {
+            CurPrompt::Notice => {
+                let r = self.prompt.handle_event(event, &mut ());
+                if let HandleRes::Exit(_) = r {
+                    self.cur_prompt = CurPrompt::None;
+                    return HandleRes::ReDraw;
+                }
+                return r;
+            }
+            CurPrompt::Create => {
+                let r = self.prompt.handle_event(event, &mut ());
+                if let HandleRes::Exit(confirmed) = r {
+                    if !confirmed {
+                        self.cur_prompt = CurPrompt::None;
+                    } else if let Err(e) = self.create_alias(state) {
+                        self.prompt.error = e;
+                    } else if let CurPrompt::Create = self.cur_prompt {
+                        self.cur_prompt = CurPrompt::None;
+                    }
+                    return HandleRes::ReDraw;
+                }
+                return r;
+            }
+            CurPrompt::ConfirmDelete(ref alias) => {
+                let alias = alias.clone();
+                let r = self.prompt.handle_event(event, &mut ());
+                if let HandleRes::Exit(confirmed) = r {
+                    self.cur_prompt = CurPrompt::None;
+                    if confirmed {
+                        self.delete_alias(alias.as_str(), state);
+                    }
+                    return HandleRes::ReDraw;
+                }
+                return r;
+            }
+            CurPrompt::Resolve => {
+                let r = self.prompt.handle_event(event, &mut ());
+                if let HandleRes::Exit(confirmed) = r {
+                    self.cur_prompt = CurPrompt::None;
+                    if confirmed {
+                        self.resolve_alias(state);
+                    }
+                    return HandleRes::ReDraw;
+                }
+                return r;
+            }
+            CurPrompt::Resolved(ref room_id) => {
+                let room_id = room_id.clone();
+                let r = self.prompt.handle_event(event, &mut ());
+                if let HandleRes::Exit(go) = r {
+                    self.cur_prompt = CurPrompt::None;
+                    if go {
+                        state.goto(Goto::Room(room_id));
+                    }
+                    return HandleRes::ReDraw;
+                }
+                return r;
+            }
+            CurPrompt::None => {}
+        }
+
+        let key = match event {
+            Event::Key(k) => k,
+            _ => return HandleRes::Ignored,
+        };
+
+        let r = self.table.handle_event(key);
+        if !matches!(r, HandleRes::Ignored) {
+            return r;
+        }
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => HandleRes::Exit(self.changed),
+            KeyCode::F(5) => {
+                self.reload(&mut state.backend);
+                HandleRes::ReDraw
+            }
+            KeyCode::Char('n') => {
+                self.prompt.clear();
+                self.prompt.msg = format!("Add a local alias to {}", self.room_id);
+                self.prompt
+                    .fields
+                    .push(("Alias".into(), Editable::string("#")));
+                self.prompt.true_button.push_str("Create");
+                self.prompt.false_button.push_str("Cancel");
+                self.cur_prompt = CurPrompt::Create;
+                HandleRes::ReDraw
+            }
+            KeyCode::Char('d') | KeyCode::Delete => {
+                if let Some(alias) = self.cur_alias() {
+                    self.prompt.clear();
+                    self.prompt.msg = format!(
+                        "Delete the alias {} ?\nThe canonical alias of the room is not updated",
+                        alias
+                    );
+                    self.prompt.true_button.push_str("Delete");
+                    self.prompt.false_button.push_str("Cancel");
+                    self.cur_prompt = CurPrompt::ConfirmDelete(alias);
+                }
+                HandleRes::ReDraw
+            }
+            KeyCode::Char('r') => {
+                self.prompt.clear();
+                self.prompt.msg.push_str("Find the room an alias points to");
+                self.prompt.fields.push((
+                    "Alias".into(),
+                    Editable::string(self.cur_alias().unwrap_or_default().as_str()),
+                ));
+                self.prompt.true_button.push_str("Resolve");
+                self.prompt.false_button.push_str("Cancel");
+                self.cur_prompt = CurPrompt::Resolve;
+                HandleRes::ReDraw
+            }
+            _ => HandleRes::Ignored,
+        }
+    }
+
+    fn hints(&self) -> Vec<(&'static str, &'static str)> {
+        vec![
+            ("n", "New alias"),
+            ("d", "Delete alias"),
+            ("r", "Resolve alias"),
+            ("F5", "Reload"),
+            ("Esc", "Back"),
+        ]
+    }
+}
@@ -0,0 +1,723 @@
+use std::time::{Duration, Instant};
+
+use crossterm::event::{Event, KeyCode};
+use tui::{
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph, Tabs, Wrap},
+};
+
+use crate::{
+    backend::{ForwardExtremityV1, PurgeHistoryV1, RoomInfoV1, UserFilter},
+    common::{
+        copy_to_clipboard,
+        editable::{Editable, EditableWidget},
+        human::format_ts,
+        picker::Picker,
+        prompt::Prompt,
+        table::EditTable,
+        tree::{Tree, TreeNode},
+        HandleRes, ViewImpl,
+    },
+    state::State,
+    views::{RoomAliasesView, RoomMessagesView},
+};
+
+#[derive(Default)]
+enum CurPrompt {
+    #[default]
+    None,
+    Notice,
+    ConfirmDeleteExtremities,
+    ConfirmPick(UserAction, String),
+    PurgeHistory,
+}
+
+/// Actions applied to a user picked from the local users
+#[derive(Clone, Copy)]
+enum UserAction {
+    MakeAdmin,
+    ForceJoin,
+}
+impl UserAction {
+    fn desc(&self) -> &'static str {
+        match self {
+            Self::MakeAdmin => "Make room admin",
+            Self::ForceJoin => "Force-join",
+        }
+    }
+}
+
+/// Parts of the room shown one at a time, cycled with `[` and `]`
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Section {
+    Details,
+    Members,
+    State,
+    Extremities,
+}
+const SECTIONS: [(Section, &str); 4] = [
+    (Section::Details, "Details"),
+    (Section::Members, "Members"),
+    (Section::State, "State"),
+    (Section::Extremities, "Forward extremities"),
+];
+
+const EXTREMITY_COLUMNS: [&str; 4] = ["Event", "Depth", "State group", "Received"];
+/// Number of users listed by the user picker
+const PICKER_SIZE: usize = 50;
+/// How often the status of a running purge is fetched
+const PURGE_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Everything the server knows about a room
+pub struct RoomDetailView {
+    room_id: String,
+    cur_prompt: CurPrompt,
+    prompt: Prompt,
+    section: Section,
+    details: Option<RoomInfoV1>,
+    /// Sections are fetched the first time they are shown
+    members: Option<EditTable>,
+    state: Option<Tree>,
+    extremities: Option<EditTable>,
+    /// Result of the last action
+    status: String,
+    /// Local user being chosen for an action
+    picker: Option<(UserAction, Picker)>,
+    /// Id of the running history purge and when its status was last fetched
+    purge: Option<(String, Instant)>,
+    /// View opened on top of the room
+    sub_view: Option<Box<dyn ViewImpl<State>>>,
+    /// Error of a finished purge, shown once the prompts opened meanwhile are closed
+    pending_notice: Option<String>,
+}
+
+impl RoomDetailView {
+    pub fn new(room_id: &str) -> Self {
+        Self {
+            room_id: room_id.to_string(),
+            cur_prompt: CurPrompt::default(),
+            prompt: Prompt::default(),
+            section: Section::Details,
+            details: None,
+            members: None,
+            state: None,
+            extremities: None,
+            status: String::new(),
+            picker: None,
+            purge: None,
+            sub_view: None,
+            pending_notice: None,
+        }
+    }
+
+    /// Fetches the current section again
+    fn reload(&mut self, state: &mut State) {
+        if let Err(e) = self.fetch(state) {
+            self.prompt.error_notice(e.as_str());
+            self.cur_prompt = CurPrompt::Notice;
+        }
+    }
+
+    fn fetch(&mut self, state: &mut State) -> Result<(), String> {
+        let synapse = &mut state.backend;
+        let room_id = self.room_id.as_str();
+        match self.section {
+            Section::Details => synapse.room_details(room_id).map(|d| {
+                self.details = Some(d);
+            }),
+            Section::Members => synapse.room_members(room_id).map(|members| {
+                let mut table = EditTable::new(&["Member"]);
+                for m in members.iter() {
+                    table.push(vec![Editable::ro_string(m.as_str())]);
+                }
+                self.members = Some(table);
+            }),
+            Section::State => synapse.room_state(room_id).map(|events| {
+                let roots = events.iter().map(state_event_node).collect();
+                self.state = Some(Tree::new(roots));
+            }),
+            Section::Extremities => synapse.forward_extremities(room_id).map(|extremities| {
+                let mut table = EditTable::new(&EXTREMITY_COLUMNS);
+                for e in extremities.iter() {
+                    table.push(extremity_row(e));
+                }
+                self.extremities = Some(table);
+            }),
+        }
+    }
+
+    fn loaded(&self) -> bool {
+        match self.section {
+            Section::Details => self.details.is_some(),
+            Section::Members => self.members.is_some(),
+            Section::State => self.state.is_some(),
+            Section::Extremities => self.extremities.is_some(),
+        }
+    }
+
+    fn switch_section(&mut self, offset: isize, state: &mut State) {
+        let idx = SECTIONS
+            .iter()
+            .position(|(s, _)| *s == self.section)
+            .unwrap_or(0) as isize;
+        let idx = (idx + offset).rem_euclid(SECTIONS.len() as isize) as usize;
+        self.section = SECTIONS[idx].0;
+        self.status.clear();
+        if !self.loaded() {
+            self.reload(state);
+        }
+    }
+
+    fn delete_extremities(&mut self, state: &mut State) {
+        match state
+            .backend
+            .delete_forward_extremities(self.room_id.as_str())
+        {
+            Ok(n) => {
+                self.status = format!("Deleted {} forward extremities", n);
+                self.reload(state);
+            }
+            Err(e) => {
+                self.prompt.error_notice(e.as_str());
+                self.cur_prompt = CurPrompt::Notice;
+            }
+        }
+    }
+
+    fn confirm_purge(&mut self) {
+        self.prompt.clear();
+        self.prompt.msg = format!(
+            "Purge the history of {}\nEvents before the event or the date are deleted from the database,\nthe event id wins if both are set. Dates as YYYY-MM-DD [HH:MM] (UTC) or ages like 30d",
+            self.room_id
+        );
+        self.prompt.error.push_str(
+            "WARNING : deleting local events removes messages sent by our users for good,\nno other server can send them back. Only check it if you mean it",
+        );
+        self.prompt
+            .fields
+            .push(("Up to event id".into(), Editable::string("")));
+        self.prompt
+            .fields
+            .push(("Or up to date".into(), Editable::date(None)));
+        self.prompt
+            .fields
+            .push(("Delete local events".into(), Editable::bool(false)));
+        self.prompt.true_button.push_str("Purge");
+        self.prompt.false_button.push_str("Cancel");
+        self.cur_prompt = CurPrompt::PurgeHistory;
+    }
+
+    fn start_purge(&mut self, state: &mut State) -> Result<(), String> {
+        let fields = &self.prompt.fields;
+        let event_id = fields[0].1.as_str().trim();
+        let purge = PurgeHistoryV1 {
+            delete_local_events: fields[2].1.as_str() == "true",
+            purge_up_to_event_id: if event_id.is_empty() {
+                None
+            } else {
+                Some(event_id.to_string())
+            },
+            purge_up_to_ts: if event_id.is_empty() {
+                Some(fields[1].1.as_ts()?.ok_or("Set an event id or a date")?)
+            } else {
+                None
+            },
+        };
+        let purge_id = state.backend.purge_history(self.room_id.as_str(), &purge)?;
+        self.status = format!("Purging history ({}) : started", purge_id);
+        self.purge = Some((purge_id, Instant::now()));
+        Ok(())
+    }
+
+    /// Fetches the status of the running purge from time to time
+    fn poll_purge(&mut self, state: &mut State) -> HandleRes {
+        let (purge_id, last_poll) = match self.purge.as_mut() {
+            Some(p) => p,
+            None => return HandleRes::Ignored,
+        };
+        if last_poll.elapsed() < PURGE_POLL_INTERVAL {
+            return HandleRes::Ignored;
+        }
+        *last_poll = Instant::now();
+        let status = match state.backend.purge_history_status(purge_id.as_str()) {
+            Ok(s) => s,
+            Err(e) => {
+                self.status = format!("Purging history ({}) : {}", purge_id, e);
+                return HandleRes::ReDraw;
+            }
+        };
+        self.status = format!("Purging history ({}) : {}", purge_id, status.status);
+        match status.status.as_str() {
+            "complete" => {
+                self.purge = None;
+                self.state = None;
+                self.extremities = None;
+                if !self.loaded() {
+                    self.pending_notice = self.fetch(state).err();
+                }
+            }
+            "failed" => {
+                self.purge = None;
+                self.pending_notice = Some(format!(
+                    "Purging the history failed : {}",
+                    status.error.as_deref().unwrap_or("unknown error")
+                ));
+            }
+            _ => {}
+        }
+        HandleRes::ReDraw
+    }
+
+    /// Shows the queued purge error, unless the user is busy with a prompt
+    fn show_pending_notice(&mut self) -> HandleRes {
+        if !matches!(self.cur_prompt, CurPrompt::None) || self.picker.is_some() {
+            return HandleRes::Ignored;
+        }
+        match self.pending_notice.take() {
+            Some(msg) => {
+                self.prompt.error_notice(msg.as_str());
+                self.cur_prompt = CurPrompt::Notice;
+                HandleRes::ReDraw
+            }
+            None => HandleRes::Ignored,
+        }
+    }
+
+    fn open_picker(&mut self, action: UserAction) {
+        let title = format!("{} : pick a local user", action.desc());
+        self.picker = Some((action, Picker::new(title)));
+    }
+
+    fn apply_user_action(&mut self, action: UserAction, user_id: &str, state: &mut State) {
+        let room_id = self.room_id.as_str();
+        let r = match action {
+            UserAction::MakeAdmin => state.backend.make_room_admin(room_id, user_id),
+            UserAction::ForceJoin => state.backend.force_join(room_id, user_id).map(|_| ()),
+        };
+        match r {
+            Ok(_) => {
+                self.status = format!("{} : done for {}", action.desc(), user_id);
+                // The members and state changed
+                self.members = None;
+                self.state = None;
+                if !self.loaded() {
+                    self.reload(state);
+                }
+            }
+            Err(e) => {
+                self.prompt.error_notice(e.as_str());
+                self.cur_prompt = CurPrompt::Notice;
+            }
+        }
+    }
+
+    fn copy_focused(&mut self) {
+        let data = match self.state.as_ref().and_then(|t| t.focused()) {
+            Some(n) => n.data.clone(),
+            None => return,
+        };
+        self.status = match copy_to_clipboard(data.as_str()) {
+            Ok(_) => "Copied the focused value to the clipboard".into(),
+            Err(e) => format!("Failed to copy : {}", e),
+        };
+    }
+
+    fn draw_details(&self) -> Vec<Spans<'static>> {
+        let mut lines = Vec::new();
+        if let Some(d) = self.details.as_ref() {
+            let opt = |v: &Option<String>| v.clone().unwrap_or_else(|| "-".into());
+            let fields = [
+                ("Name", opt(&d.name)),
+                ("Topic", opt(&d.topic)),
+                ("Canonical alias", opt(&d.canonical_alias)),
+                ("Creator", opt(&d.creator)),
+                ("Version", opt(&d.version)),
+                ("Type", opt(&d.room_type)),
+                (
+                    "Members",
+                    format!("{} ({} local)", d.joined_members, d.joined_local_members),
+                ),
+                ("State events", d.state_events.to_string()),
+                ("Public", d.public.to_string()),
+                ("Federatable", d.federatable.to_string()),
+                ("Encryption", opt(&d.encryption)),
+                ("Join rules", opt(&d.join_rules)),
+                ("Guest access", opt(&d.guest_access)),
+                ("History visibility", opt(&d.history_visibility)),
+            ];
+            for (name, value) in fields {
+                lines.push(Spans::from(vec![
+                    Span::styled(
+                        format!("{:>20} : ", name),
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                    Span::raw(value),
+                ]));
+            }
+        }
+        lines
+    }
+}
+
+/// Node of a state event, labelled by its type and state key
+pub(super) fn state_event_node(event: &serde_json::Value) -> TreeNode {
+    let field = |name: &str| event.get(name).and_then(|v| v.as_str()).unwrap_or("");
+    let state_key = field("state_key");
+    let label = if state_key.is_empty() {
+        field("type").to_string()
+    } else {
+        format!("{} {}", field("type"), state_key)
+    };
+    let mut node = TreeNode::from_json("", event);
+    node.label = label;
+    node
+}
+
+fn extremity_row(e: &ForwardExtremityV1) -> Vec<Editable> {
+    vec![
+        Editable::ro_string(e.event_id.as_str()),
+        Editable::ro_string(e.depth.to_string().as_str()),
+        Editable::ro_string(
+            e.state_group
+                .map(|g| g.to_string())
+                .unwrap_or_default()
+                .as_str(),
+        ),
+        Editable::ro_string(format_ts(e.received_ts).as_str()),
+    ]
+}
+
+impl ViewImpl<State> for RoomDetailView {
+    fn title(&self) -> &'static str {
+        "Room"
+    }
+
+    fn enter_view(&mut self, state: &mut State) {
+        self.reload(state);
+    }
+
+    fn draw_view(
+        &mut self,
+        frame: &mut tui::Frame<tui::backend::CrosstermBackend<&mut std::io::Stdout>>,
+        rect: tui::layout::Rect,
+        state: &mut State,
+    ) {
+        if let Some(v) = self.sub_view.as_mut() {
+            v.draw_view(frame, rect, state);
+            return;
+        }
+        if !matches!(self.cur_prompt, CurPrompt::None) {
+            self.prompt.draw_view(frame, rect, &mut ());
+            return;
+        }
+        if let Some((_, picker)) = self.picker.as_mut() {
+            picker.draw(frame, rect);
+            return;
+        }
+
+        let rects = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(0)])
+            .split(rect);
+        let selected = SECTIONS
+            .iter()
+            .position(|(s, _)| *s == self.section)
+            .unwrap_or(0);
+        let mut titles: Vec<Spans> = SECTIONS.iter().map(|(_, t)| Spans::from(*t)).collect();
+        if !self.status.is_empty() {
+            titles.push(Spans::from(Span::styled(
+                self.status.as_str(),
+                Style::default().fg(Color::Yellow),
+            )));
+        }
+        frame.render_widget(
+            Tabs::new(titles)
+                .select(selected)
+                .style(Style::default().fg(Color::DarkGray))
+                .highlight_style(
+                    Style::default()
+                        .fg(Color::White)
+                        .add_modifier(Modifier::BOLD),
+                )
+                .divider("|"),
+            rects[0],
+        );
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(self.room_id.as_str());
+        match self.section {
+            Section::Details => frame.render_widget(
+                Paragraph::new(self.draw_details())
+                    .wrap(Wrap { trim: false })
+                    .block(block),
+                rects[1],
+            ),
+            Section::Members => {
+                if let Some(t) = self.members.as_mut() {
+                    let title = format!("{} : {} members", self.room_id, t.len());
+                    t.draw(frame, rects[1], block.title(title));
+                }
+            }
+            Section::State => {
+                if let Some(t) = self.state.as_mut() {
+                    let title = format!("{} : {} state events", self.room_id, t.roots.len());
+                    t.draw(frame, rects[1], block.title(title));
+                }
+            }
+            Section::Extremities => {
+                if let Some(t) = self.extremities.as_mut() {
+                    let title = format!("{} : {} forward extremities", self.room_id, t.len());
+                    t.draw(frame, rects[1], block.title(title));
+                }
+            }
+        }
+    }
+
+    fn handle_event(&mut self, event: &Event, state: &mut State) -> HandleRes {
+        if let Some(v) = self.sub_view.as_mut() {
+            let r = v.handle_event(event, state);
+            if let HandleRes::Exit(changed) = r {
+                self.sub_view = None;
+                if changed {
+                    // Deleting an alias also removes it from the canonical alias event
+                    self.details = None;
+                    self.state = None;
+                    if !self.loaded() {
+                        self.reload(state);
+                    }
+                }
+                return HandleRes::ReDraw;
+            }
+            return r;
+        }
+        match self.cur_prompt {
+            CurPrompt::Notice => {
+                let r = self.prompt.handle_event(event, &mut ());
+                if let HandleRes::Exit(_) = r {
+                    self.cur_prompt = CurPrompt::None;
+                    return HandleRes::ReDraw;
+                }
+                return r;
+            }
+            CurPrompt::ConfirmDeleteExtremities => {
+                let r = self.prompt.handle_event(event, &mut ());
+                if let HandleRes::Exit(confirmed) = r {
+                    self.cur_prompt = CurPrompt::None;
+                    if confirmed {
+                        self.delete_extremities(state);
+                    }
+                    return HandleRes::ReDraw;
+                }
+                return r;
+            }
+            CurPrompt::PurgeHistory => {
+                let r = self.prompt.handle_event(event, &mut ());
+                if let HandleRes::Exit(confirmed) = r {
+                    if !confirmed {
+                        self.cur_prompt = CurPrompt::None;
+                    } else if let Err(e) = self.start_purge(state) {
+                        self.prompt.error = e;
+                    } else {
+                        self.cur_prompt = CurPrompt::None;
+                    }
+                    return HandleRes::ReDraw;
+                }
+                return r;
+            }
+            CurPrompt::ConfirmPick(action, ref user_id) => {
+                let user_id = user_id.clone();
+                let r = self.prompt.handle_event(event, &mut ());
+                if let HandleRes::Exit(confirmed) = r {
+                    self.cur_prompt = CurPrompt::None;
+                    if confirmed {
+                        self.apply_user_action(action, user_id.as_str(), state);
+                    }
+                    return HandleRes::ReDraw;
+                }
+                return r;
+            }
+            CurPrompt::None => {}
+        }
+
+        let key = match event {
+            Event::Key(k) => k,
+            _ => return HandleRes::Ignored,
+        };
+
+        if let Some((action, picker)) = self.picker.as_mut() {
+            let r = picker.handle_event(key);
+            if let HandleRes::Exit(picked) = r {
+                if let (true, Some(user_id)) = (picked, picker.picked()) {
+                    self.prompt.clear();
+                    self.prompt.msg = match action {
+                        UserAction::MakeAdmin => format!(
+                            "Make {} admin of {} ?\nThe user gets the highest power level, granted by a local room admin",
+                            user_id, self.room_id
+                        ),
+                        UserAction::ForceJoin => format!(
+                            "Join {} to {} ?\nThe room must be public or the user invited",
+                            user_id, self.room_id
+                        ),
+                    };
+                    self.prompt.true_button.push_str("Confirm");
+                    self.prompt.false_button.push_str("Cancel");
+                    self.cur_prompt = CurPrompt::ConfirmPick(*action, user_id.to_string());
+                }
+                self.picker = None;
+                return HandleRes::ReDraw;
+            }
+            return r;
+        }
+
+        let r = match self.section {
+            Section::Details => HandleRes::Ignored,
+            Section::Members => self
+                .members
+                .as_mut()
+                .map(|t| t.handle_event(key))
+                .unwrap_or(HandleRes::Ignored),
+            Section::State => self
+                .state
+                .as_mut()
+                .map(|t| t.handle_event(key))
+                .unwrap_or(HandleRes::Ignored),
+            Section::Extremities => self
+                .extremities
+                .as_mut()
+                .map(|t| t.handle_event(key))
+                .unwrap_or(HandleRes::Ignored),
+        };
+        if !matches!(r, HandleRes::Ignored) {
+            return r;
+        }
+
+        match (self.section, key.code) {
+            (_, KeyCode::Esc | KeyCode::Char('q')) => HandleRes::Exit(false),
+            (_, KeyCode::F(5)) => {
+                self.reload(state);
+                HandleRes::ReDraw
+            }
+            (_, KeyCode::Char(']')) => {
+                self.switch_section(1, state);
+                HandleRes::ReDraw
+            }
+            (_, KeyCode::Char('[')) => {
+                self.switch_section(-1, state);
+                HandleRes::ReDraw
+            }
+            (Section::State, KeyCode::Char('e')) => {
+                if let Some(t) = self.state.as_mut() {
+                    let expanded = t.roots.iter().any(|n| n.expanded);
+                    t.expand_all(!expanded);
+                }
+                HandleRes::ReDraw
+            }
+            (Section::State, KeyCode::Char('c')) => {
+                self.copy_focused();
+                HandleRes::ReDraw
+            }
+            (_, KeyCode::Char('P')) => {
+                if self.purge.is_some() {
+                    return HandleRes::Handled;
+                }
+                self.confirm_purge();
+                HandleRes::ReDraw
+            }
+            (_, KeyCode::Char('a')) => {
+                let mut view = RoomAliasesView::new(self.room_id.as_str());
+                view.enter_view(state);
+                self.sub_view = Some(Box::new(view));
+                HandleRes::ReDraw
+            }
+            (_, KeyCode::Char('m')) => {
+                let mut view = RoomMessagesView::new(self.room_id.as_str());
+                view.enter_view(state);
+                self.sub_view = Some(Box::new(view));
+                HandleRes::ReDraw
+            }
+            (_, KeyCode::Char('A')) => {
+                self.open_picker(UserAction::MakeAdmin);
+                HandleRes::ReDraw
+            }
+            (_, KeyCode::Char('J')) => {
+                self.open_picker(UserAction::ForceJoin);
+                HandleRes::ReDraw
+            }
+            (Section::Extremities, KeyCode::Char('x')) => {
+                let count = self.extremities.as_ref().map(|t| t.len()).unwrap_or(0);
+                self.prompt.clear();
+                self.prompt.msg = format!(
+                    "Delete the forward extremities of {} ({} now) ?\nOnly the most recent ones are kept. This is meant for rooms\nslowed down by too many extremities, do not do it without reason",
+                    self.room_id, count
+                );
+                self.prompt.true_button.push_str("Delete");
+                self.prompt.false_button.push_str("Cancel");
+                self.cur_prompt = CurPrompt::ConfirmDeleteExtremities;
+                HandleRes::ReDraw
+            }
+            _ => HandleRes::Ignored,
+        }
+    }
+
+    fn tick(&mut self, state: &mut State) -> HandleRes {
+        if let Some(v) = self.sub_view.as_mut() {
+            return v.tick(state);
+        }
+        let r = match (self.poll_purge(state), self.show_pending_notice()) {
+            (HandleRes::Ignored, r) => r,
+            (r, _) => r,
+        };
+        let picker = match self.picker.as_mut() {
+            Some((_, p)) => p,
+            None => return r,
+        };
+        let query = match picker.pending_search() {
+            Some(q) => q,
+            None => return r,
+        };
+        let filter = UserFilter {
+            name: query.clone(),
+            ..Default::default()
+        };
+        match state.backend.list_users(0, PICKER_SIZE, &filter) {
+            Ok(users) => picker.set_items(
+                query,
+                users.into_iter().map(|u| (u.name, u.displayname)).collect(),
+            ),
+            Err(e) => picker.set_error(query, e),
+        }
+        HandleRes::ReDraw
+    }
+
+    fn hints(&self) -> Vec<(&'static str, &'static str)> {
+        if let Some(v) = self.sub_view.as_ref() {
+            return v.hints();
+        }
+        if self.picker.is_some() {
+            return vec![("↑/↓", "Choose"), ("Enter", "Pick"), ("Esc", "Cancel")];
+        }
+        let mut hints = vec![("[/]", "Switch section")];
+        match self.section {
+            Section::State => hints.extend([
+                ("Enter/←/→", "Collapse/Expand"),
+                ("e", "Expand all"),
+                ("c", "Copy value"),
+            ]),
+            Section::Extremities => hints.push(("x", "Delete extremities")),
+            _ => {}
+        }
+        hints.extend([
+            ("a", "Aliases"),
+            ("m", "Messages"),
+            ("A", "Make room admin"),
+            ("J", "Force-join user"),
+            ("P", "Purge history"),
+            ("F5", "Reload"),
+            ("Esc", "Back"),
+        ]);
+        hints
+    }
+}
@@ -0,0 +1,255 @@
+use crossterm::event::{Event, KeyCode};
+use tui::{
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Style},
+    text::Span,
+    widgets::{Block, Borders, Paragraph},
+};
+
+use crate::{
+    backend::{RoomFilter, Synapse},
+    common::{
+        editable::{Editable, EditableWidget},
+        prompt::Prompt,
+        table::EditTable,
+        Goto, HandleRes, ViewImpl,
+    },
+    state::State,
+};
+
+#[derive(Default)]
+enum CurPrompt {
+    #[default]
+    None,
+    Notice,
+}
+
+const DIRECTORY_COLUMNS: [&str; 6] = [
+    "Room",
+    "Name",
+    "Alias",
+    "Members",
+    "In directory",
+    "Hosted & public",
+];
+const IN_DIRECTORY_COLUMN: usize = 4;
+const HOSTED_PUBLIC_COLUMN: usize = 5;
+const PAGE_SIZE: usize = 100;
+
+/// Compares the public room directory with the rooms the server reports as public
+pub struct RoomDirectoryView {
+    cur_prompt: CurPrompt,
+    prompt: Prompt,
+    table: EditTable,
+    /// Whether a room was published or unpublished
+    changed: bool,
+}
+
+impl Default for RoomDirectoryView {
+    fn default() -> Self {
+        Self {
+            cur_prompt: CurPrompt::default(),
+            prompt: Prompt::default(),
+            table: EditTable::new(&DIRECTORY_COLUMNS),
+            changed: false,
+        }
+    }
+}
+
+impl RoomDirectoryView {
+    fn reload(&mut self, synapse: &mut Synapse) {
+        self.table.clear();
+        if let Err(e) = self.load(synapse) {
+            self.prompt.error_notice(e.as_str());
+            self.cur_prompt = CurPrompt::Notice;
+        }
+    }
+
+    /// Fetches the whole directory and the hosted public rooms and merges them by id
+    fn load(&mut self, synapse: &mut Synapse) -> Result<(), String> {
+        let mut since: Option<String> = None;
+        loop {
+            let page = synapse.public_rooms(since.as_deref(), PAGE_SIZE)?;
+            for r in page.chunk {
+                self.table.push(vec![
+                    Editable::ro_string(r.room_id.as_str()),
+                    Editable::ro_string(r.name.unwrap_or_default().as_str()),
+                    Editable::ro_string(r.canonical_alias.unwrap_or_default().as_str()),
+                    Editable::ro_string(r.num_joined_members.to_string().as_str()),
+                    Editable::bool(true),
+                    Editable::ro_bool(false),
+                ]);
+            }
+            match page.next_batch {
+                Some(next) if Some(&next) != since.as_ref() => since = Some(next),
+                _ => break,
+            }
+        }
+
+        let filter = RoomFilter {
+            public_only: true,
+            ..Default::default()
+        };
+        let mut offset = 0;
+        loop {
+            let rooms = synapse.list_rooms(offset, PAGE_SIZE, &filter)?;
+            offset += rooms.len();
+            let last = rooms.len() < PAGE_SIZE;
+            for r in rooms {
+                match self.find_room(r.room_id.as_str()) {
+                    Some(y) => self.table.rows[y][HOSTED_PUBLIC_COLUMN] = Editable::ro_bool(true),
+                    None => self.table.push(vec![
+                        Editable::ro_string(r.room_id.as_str()),
+                        Editable::ro_string(r.name.unwrap_or_default().as_str()),
+                        Editable::ro_string(r.canonical_alias.unwrap_or_default().as_str()),
+                        Editable::ro_string(r.joined_members.to_string().as_str()),
+                        Editable::bool(false),
+                        Editable::ro_bool(true),
+                    ]),
+                }
+            }
+            if last {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    fn find_room(&self, room_id: &str) -> Option<usize> {
+        (0..self.table.len()).find(|y| self.table.rows[*y][0].as_str() == room_id)
+    }
+
+    fn apply_edit(&mut self, x: usize, y: usize, synapse: &mut Synapse) {
+        if x != IN_DIRECTORY_COLUMN {
+            return;
+        }
+        let room_id = self.table.rows[y][0].as_str().to_string();
+        let cell = &mut self.table.rows[y][x];
+        match synapse.set_room_published(room_id.as_str(), cell.as_str() == "true") {
+            Ok(_) => {
+                cell.forget_orig();
+                self.changed = true;
+            }
+            Err(e) => {
+                cell.restore_orig();
+                self.prompt.error_notice(e.as_str());
+                self.cur_prompt = CurPrompt::Notice;
+            }
+        }
+    }
+
+    fn status_line(&self) -> String {
+        let count = |x: usize| {
+            self.table
+                .rows
+                .iter()
+                .filter(|r| r[x].as_str() == "true")
+                .count()
+        };
+        let mismatches = self
+            .table
+            .rows
+            .iter()
+            .filter(|r| r[IN_DIRECTORY_COLUMN].as_str() != r[HOSTED_PUBLIC_COLUMN].as_str())
+            .count();
+        format!(
+            "{} rooms in the directory, {} hosted public rooms, {} mismatches",
+            count(IN_DIRECTORY_COLUMN),
+            count(HOSTED_PUBLIC_COLUMN),
+            mismatches
+        )
+    }
+}
+
+impl ViewImpl<State> for RoomDirectoryView {
+    fn title(&self) -> &'static str {
+        "Room directory"
+    }
+
+    fn enter_view(&mut self, state: &mut State) {
+        self.reload(&mut state.backend);
+    }
+
+    fn draw_view(
+        &mut self,
+        frame: &mut tui::Frame<tui::backend::CrosstermBackend<&mut std::io::Stdout>>,
+        rect: tui::layout::Rect,
+        _state: &mut State,
+    ) {
+        if !matches!(self.cur_prompt, CurPrompt::None) {
+            self.prompt.draw_view(frame, rect, &mut ());
+            return;
+        }
+
+        let rects = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(0)])
+            .split(rect);
+        frame.render_widget(
+            Paragraph::new(Span::styled(
+                self.status_line(),
+                Style::default().fg(Color::Yellow),
+            )),
+            rects[0],
+        );
+        self.table.draw(
+            frame,
+            rects[1],
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Room directory"),
+        );
+    }
+
+    fn handle_event(&mut self, event: &Event, state: &mut State) -> HandleRes {
+        if let CurPrompt::Notice = self.cur_prompt {
+            let r = self.prompt.handle_event(event, &mut ());
+            if let HandleRes::Exit(_) = r {
+                self.cur_prompt = CurPrompt::None;
+                return HandleRes::ReDraw;
+            }
+            return r;
+        }
+
+        let key = match event {
+            Event::Key(k) => k,
+            _ => return HandleRes::Ignored,
+        };
+
+        // Enter toggles the directory cell and opens the room anywhere else
+        if let KeyCode::Enter = key.code {
+            if !matches!(self.table.cur_item(), Some(i) if i.is_editable()) {
+                if let Some(y) = self.table.cur_row() {
+                    state.goto(Goto::Room(self.table.rows[y][0].as_str().to_string()));
+                }
+                return HandleRes::ReDraw;
+            }
+        }
+
+        let r = self.table.handle_event(key);
+        if let Some((x, y)) = self.table.take_committed() {
+            self.apply_edit(x, y, &mut state.backend);
+            return HandleRes::ReDraw;
+        }
+        if !matches!(r, HandleRes::Ignored) {
+            return r;
+        }
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => HandleRes::Exit(self.changed),
+            KeyCode::F(5) => {
+                self.reload(&mut state.backend);
+                HandleRes::ReDraw
+            }
+            _ => HandleRes::Ignored,
+        }
+    }
+
+    fn hints(&self) -> Vec<(&'static str, &'static str)> {
+        vec![
+            ("Enter", "Go to room / toggle"),
+            ("F5", "Reload"),
+            ("Esc", "Back"),
+        ]
+    }
+}
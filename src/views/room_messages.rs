@@ -0,0 +1,439 @@
+use crossterm::event::{Event, KeyCode};
+use serde_json::{json, Value};
+use tui::{
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Style},
+    text::Span,
+    widgets::{Block, Borders, Paragraph},
+};
+
+use crate::{
+    backend::Synapse,
+    common::{
+        editable::{Editable, EditableWidget},
+        human::format_ts,
+        prompt::Prompt,
+        table::EditTable,
+        HandleRes, ViewImpl,
+    },
+    state::State,
+    views::EventContextView,
+};
+
+#[derive(Default)]
+enum CurPrompt {
+    #[default]
+    None,
+    Notice,
+    Filter,
+    Redact(String),
+}
+
+const MESSAGE_COLUMNS: [&str; 4] = ["Sender", "Time", "Type", "Body"];
+const PAGE_SIZE: usize = 50;
+
+/// Event filter of the timeline, each field a comma separated list
+#[derive(Default)]
+struct MessageFilter {
+    senders: String,
+    types: String,
+    not_types: String,
+}
+
+impl MessageFilter {
+    fn is_empty(&self) -> bool {
+        self.senders.is_empty() && self.types.is_empty() && self.not_types.is_empty()
+    }
+
+    /// The filter as a RoomEventFilter
+    fn to_json(&self) -> Option<Value> {
+        if self.is_empty() {
+            return None;
+        }
+        let list = |s: &str| -> Vec<String> {
+            s.split(',')
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+                .collect()
+        };
+        let mut filter = json!({});
+        if !self.senders.is_empty() {
+            filter["senders"] = json!(list(self.senders.as_str()));
+        }
+        if !self.types.is_empty() {
+            filter["types"] = json!(list(self.types.as_str()));
+        }
+        if !self.not_types.is_empty() {
+            filter["not_types"] = json!(list(self.not_types.as_str()));
+        }
+        Some(filter)
+    }
+}
+
+/// Read-only timeline of a room, a page at a time
+pub struct RoomMessagesView {
+    room_id: String,
+    cur_prompt: CurPrompt,
+    prompt: Prompt,
+    table: EditTable,
+    /// Events of the page, oldest first
+    events: Vec<Value>,
+    /// Token and direction the current page was fetched with
+    page: (Option<String>, bool),
+    /// Tokens to fetch the pages before and after the current one
+    older: Option<String>,
+    newer: Option<String>,
+    filter: MessageFilter,
+    /// Result of the last action
+    status: String,
+    /// Context of an event opened on top of the timeline
+    sub_view: Option<Box<dyn ViewImpl<State>>>,
+}
+
+impl RoomMessagesView {
+    pub fn new(room_id: &str) -> Self {
+        Self {
+            room_id: room_id.to_string(),
+            cur_prompt: CurPrompt::default(),
+            prompt: Prompt::default(),
+            table: EditTable::new(&MESSAGE_COLUMNS),
+            events: Vec::new(),
+            page: (None, true),
+            older: None,
+            newer: None,
+            filter: MessageFilter::default(),
+            status: String::new(),
+            sub_view: None,
+        }
+    }
+
+    /// Fetches the latest events
+    fn reload(&mut self, synapse: &mut Synapse) {
+        self.fetch(None, true, synapse);
+    }
+
+    /// Fetches the page starting at `from`, keeping the current one if there is nothing there
+    fn fetch(&mut self, from: Option<String>, backwards: bool, synapse: &mut Synapse) {
+        let filter = self.filter.to_json();
+        let page = match synapse.room_messages(
+            self.room_id.as_str(),
+            from.as_deref(),
+            backwards,
+            PAGE_SIZE,
+            filter.as_ref(),
+        ) {
+            Ok(p) => p,
+            Err(e) => {
+                self.prompt.error_notice(e.as_str());
+                self.cur_prompt = CurPrompt::Notice;
+                return;
+            }
+        };
+        if page.chunk.is_empty() && from.is_some() {
+            self.status = if backwards {
+                "No older events".into()
+            } else {
+                "No newer events".into()
+            };
+            return;
+        }
+        self.status.clear();
+        let mut events = page.chunk;
+        if backwards {
+            events.reverse();
+            self.older = page.end;
+            self.newer = Some(page.start);
+        } else {
+            self.older = Some(page.start);
+            self.newer = page.end;
+        }
+        self.page = (from, backwards);
+        self.table.clear();
+        for e in events.iter() {
+            self.table.push(event_row(e));
+        }
+        // Start at the edge of the timeline the page was reached from
+        if backwards {
+            self.table.focus_y = self.table.len().saturating_sub(1);
+        }
+        self.events = events;
+    }
+
+    /// Fetches the current page again
+    fn refresh(&mut self, synapse: &mut Synapse) {
+        let (from, backwards) = self.page.clone();
+        let focus = self.table.focus_y;
+        self.fetch(from, backwards, synapse);
+        self.table.focus_y = focus.min(self.table.len().saturating_sub(1));
+    }
+
+    fn cur_event_id(&self) -> Option<String> {
+        self.table
+            .cur_row()
+            .and_then(|y| self.events[y]["event_id"].as_str())
+            .map(|s| s.to_string())
+    }
+
+    fn redact(&mut self, event_id: &str, state: &mut State) {
+        let reason = self.prompt.fields[0].1.as_str().trim().to_string();
+        match state
+            .backend
+            .redact_event(self.room_id.as_str(), event_id, reason.as_str())
+        {
+            Ok(_) => {
+                self.refresh(&mut state.backend);
+                self.status = format!("Redacted {}", event_id);
+            }
+            Err(e) => {
+                self.prompt.error_notice(e.as_str());
+                self.cur_prompt = CurPrompt::Notice;
+            }
+        }
+    }
+
+    fn status_line(&self) -> String {
+        let mut r = format!("{} events", self.events.len());
+        let ts = |e: Option<&Value>| e.and_then(|e| e["origin_server_ts"].as_u64());
+        if let (Some(first), Some(last)) = (ts(self.events.first()), ts(self.events.last())) {
+            r.push_str(&format!(
+                " from {} to {}",
+                format_ts(first),
+                format_ts(last)
+            ));
+        }
+        if !self.filter.is_empty() {
+            r.push_str(" | filtered");
+        }
+        if !self.status.is_empty() {
+            r.push_str(" | ");
+            r.push_str(self.status.as_str());
+        }
+        r
+    }
+}
+
+/// Row of the timeline table
+pub(super) fn event_row(e: &Value) -> Vec<Editable> {
+    let field = |name: &str| e[name].as_str().unwrap_or("").to_string();
+    let body = if !e["unsigned"]["redacted_because"].is_null() {
+        "(redacted)".to_string()
+    } else if let Some(body) = e["content"]["body"].as_str() {
+        body.lines().next().unwrap_or("").to_string()
+    } else if let Some(state_key) = e["state_key"].as_str() {
+        // State events have no body, show what they apply to
+        format!("[{}] {}", state_key, e["content"])
+    } else {
+        String::new()
+    };
+    vec![
+        Editable::ro_string(field("sender").as_str()),
+        Editable::ro_string(
+            e["origin_server_ts"]
+                .as_u64()
+                .map(format_ts)
+                .unwrap_or_default()
+                .as_str(),
+        ),
+        Editable::ro_string(field("type").as_str()),
+        Editable::ro_string(body.as_str()),
+    ]
+}
+
+impl ViewImpl<State> for RoomMessagesView {
+    fn title(&self) -> &'static str {
+        "Room messages"
+    }
+
+    fn enter_view(&mut self, state: &mut State) {
+        self.reload(&mut state.backend);
+    }
+
+    fn draw_view(
+        &mut self,
+        frame: &mut tui::Frame<tui::backend::CrosstermBackend<&mut std::io::Stdout>>,
+        rect: tui::layout::Rect,
+        state: &mut State,
+    ) {
+        if let Some(v) = self.sub_view.as_mut() {
+            v.draw_view(frame, rect, state);
+            return;
+        }
+        if !matches!(self.cur_prompt, CurPrompt::None) {
+            self.prompt.draw_view(frame, rect, &mut ());
+            return;
+        }
+
+        let rects = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(0)])
+            .split(rect);
+        frame.render_widget(
+            Paragraph::new(Span::styled(
+                self.status_line(),
+                Style::default().fg(Color::Yellow),
+            )),
+            rects[0],
+        );
+        self.table.draw(
+            frame,
+            rects[1],
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("{} : messages", self.room_id)),
+        );
+    }
+
+    fn handle_event(&mut self, event: &Event, state: &mut State) -> HandleRes {
+        if let Some(v) = self.sub_view.as_mut() {
+            let r = v.handle_event(event, state);
+            if let HandleRes::Exit(_) = r {
+                self.sub_view = None;
+                return HandleRes::ReDraw;
+            }
+            return r;
+        }
+        match self.cur_prompt {
+            CurPrompt::Notice => {
+                let r = self.prompt.handle_event(event, &mut ());
+                if let HandleRes::Exit(_) = r {
+                    self.cur_prompt = CurPrompt::None;
+                    return HandleRes::ReDraw;
+                }
+                return r;
+            }
+            CurPrompt::Filter => {
+                let r = self.prompt.handle_event(event, &mut ());
+                if let HandleRes::Exit(apply) = r {
+                    self.cur_prompt = CurPrompt::None;
+                    if apply {
+                        let field = |i: usize| self.prompt.fields[i].1.as_str().trim().to_string();
+                        self.filter = MessageFilter {
+                            senders: field(0),
+                            types: field(1),
+                            not_types: field(2),
+                        };
+                        self.reload(&mut state.backend);
+                    }
+                    return HandleRes::ReDraw;
+                }
+                return r;
+            }
+            CurPrompt::Redact(ref event_id) => {
+                let event_id = event_id.clone();
+                let r = self.prompt.handle_event(event, &mut ());
+                if let HandleRes::Exit(confirmed) = r {
+                    self.cur_prompt = CurPrompt::None;
+                    if confirmed {
+                        self.redact(event_id.as_str(), state);
+                    }
+                    return HandleRes::ReDraw;
+                }
+                return r;
+            }
+            CurPrompt::None => {}
+        }
+
+        let key = match event {
+            Event::Key(k) => k,
+            _ => return HandleRes::Ignored,
+        };
+
+        if let KeyCode::Enter = key.code {
+            if let Some(event_id) = self.cur_event_id() {
+                let mut view = EventContextView::new(self.room_id.as_str(), event_id.as_str());
+                view.enter_view(state);
+                self.sub_view = Some(Box::new(view));
+            }
+            return HandleRes::ReDraw;
+        }
+
+        let r = self.table.handle_event(key);
+        if !matches!(r, HandleRes::Ignored) {
+            return r;
+        }
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => HandleRes::Exit(false),
+            KeyCode::F(5) => {
+                self.reload(&mut state.backend);
+                HandleRes::ReDraw
+            }
+            KeyCode::Char('b') => {
+                if let Some(from) = self.older.clone() {
+                    self.fetch(Some(from), true, &mut state.backend);
+                } else {
+                    self.status = "No older events".into();
+                }
+                HandleRes::ReDraw
+            }
+            KeyCode::Char('f') => {
+                if let Some(from) = self.newer.clone() {
+                    self.fetch(Some(from), false, &mut state.backend);
+                } else {
+                    self.status = "No newer events".into();
+                }
+                HandleRes::ReDraw
+            }
+            KeyCode::Char('/') => {
+                self.prompt.clear();
+                self.prompt.msg.push_str(
+                    "Filter events\nEach field is a comma separated list, types may end with *",
+                );
+                self.prompt.fields.push((
+                    "Senders".into(),
+                    Editable::string(self.filter.senders.as_str()),
+                ));
+                self.prompt
+                    .fields
+                    .push(("Types".into(), Editable::string(self.filter.types.as_str())));
+                self.prompt.fields.push((
+                    "Not types".into(),
+                    Editable::string(self.filter.not_types.as_str()),
+                ));
+                self.prompt.true_button.push_str("Apply");
+                self.prompt.false_button.push_str("Cancel");
+                self.cur_prompt = CurPrompt::Filter;
+                HandleRes::ReDraw
+            }
+            KeyCode::Char('x') => {
+                if let Some(event_id) = self.cur_event_id() {
+                    self.prompt.clear();
+                    self.prompt.msg = format!(
+                        "Redact {} ?\nThe admin account must be in the room with enough power",
+                        event_id
+                    );
+                    self.prompt
+                        .fields
+                        .push(("Reason".into(), Editable::string("")));
+                    self.prompt.true_button.push_str("Redact");
+                    self.prompt.false_button.push_str("Cancel");
+                    self.cur_prompt = CurPrompt::Redact(event_id);
+                }
+                HandleRes::ReDraw
+            }
+            _ => HandleRes::Ignored,
+        }
+    }
+
+    fn tick(&mut self, state: &mut State) -> HandleRes {
+        if let Some(v) = self.sub_view.as_mut() {
+            return v.tick(state);
+        }
+        HandleRes::Ignored
+    }
+
+    fn hints(&self) -> Vec<(&'static str, &'static str)> {
+        if let Some(v) = self.sub_view.as_ref() {
+            return v.hints();
+        }
+        vec![
+            ("Enter", "Event context"),
+            ("b", "Older"),
+            ("f", "Newer"),
+            ("/", "Filter"),
+            ("x", "Redact event"),
+            ("F5", "Latest"),
+            ("Esc", "Back"),
+        ]
+    }
+}
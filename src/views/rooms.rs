@@ -0,0 +1,547 @@
+use crossterm::event::{Event, KeyCode};
+use tui::{
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Style},
+    text::Span,
+    widgets::{Block, Borders, Paragraph},
+};
+
+use crate::{
+    backend::{RoomFilter, RoomInfoV1, Synapse},
+    common::{
+        editable::{Editable, EditableWidget},
+        prompt::Prompt,
+        table::EditTable,
+        Goto, HandleRes, ViewImpl,
+    },
+    state::State,
+    views::{EventContextView, RoomDetailView, RoomDirectoryView},
+};
+
+#[derive(Default)]
+enum CurPrompt {
+    #[default]
+    None,
+    Notice,
+    Filter,
+    Block,
+    EventContext,
+}
+
+#[derive(Default)]
+enum SyncState {
+    #[default]
+    Some,
+    Max,
+}
+
+const ROOM_COLUMNS: [&str; 7] = [
+    "ID",
+    "Name",
+    "Alias",
+    "Members",
+    "Local members",
+    "Public",
+    "Blocked",
+];
+const PUBLIC_COLUMN: usize = 5;
+const BLOCKED_COLUMN: usize = 6;
+const PAGE_SIZE: usize = 32;
+/// Number of rooms whose block status is fetched per tick
+const BLOCK_CHECKS_PER_TICK: usize = 8;
+
+pub struct RoomsView {
+    cur_prompt: CurPrompt,
+    prompt: Prompt,
+    sync_state: SyncState,
+    filter: RoomFilter,
+    /// Only keep the blocked rooms, filtered as their status is fetched
+    blocked_only: bool,
+    room_table: EditTable,
+    /// Number of rooms fetched from the server, rows may have been filtered out since
+    fetched: usize,
+    /// Number of rows whose block status was fetched
+    block_resolved: usize,
+    /// Rooms blocked from this tab, listed by the blocked filter even when the
+    /// server does not know them
+    blocked_here: Vec<String>,
+    /// View opened on top of the room list
+    sub_view: Option<Box<dyn ViewImpl<State>>>,
+}
+
+impl Default for RoomsView {
+    fn default() -> Self {
+        Self {
+            cur_prompt: CurPrompt::default(),
+            prompt: Prompt::default(),
+            sync_state: SyncState::default(),
+            filter: RoomFilter::default(),
+            blocked_only: false,
+            room_table: EditTable::new(&ROOM_COLUMNS),
+            fetched: 0,
+            block_resolved: 0,
+            blocked_here: Vec::new(),
+            sub_view: None,
+        }
+    }
+}
+
+impl ViewImpl<State> for RoomsView {
+    fn title(&self) -> &'static str {
+        "Rooms"
+    }
+
+    fn enter_view(&mut self, state: &mut State) {
+        if let Err(e) = self.load_next_chunk(&mut state.backend) {
+            self.prompt.error_notice(e.as_str());
+            self.cur_prompt = CurPrompt::Notice;
+        }
+    }
+
+    fn draw_view(
+        &mut self,
+        frame: &mut tui::Frame<tui::backend::CrosstermBackend<&mut std::io::Stdout>>,
+        rect: tui::layout::Rect,
+        state: &mut State,
+    ) {
+        if let Some(v) = self.sub_view.as_mut() {
+            v.draw_view(frame, rect, state);
+            return;
+        }
+
+        if !matches!(self.cur_prompt, CurPrompt::None) {
+            self.prompt.draw_view(frame, rect, &mut ());
+            return;
+        }
+
+        let rects = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(0)])
+            .split(rect);
+        frame.render_widget(
+            Paragraph::new(Span::styled(
+                self.status_line(),
+                Style::default().fg(Color::Yellow),
+            )),
+            rects[0],
+        );
+        self.room_table.draw(
+            frame,
+            rects[1],
+            Block::default().borders(Borders::LEFT | Borders::RIGHT),
+        );
+    }
+
+    fn handle_event(&mut self, event: &Event, state: &mut State) -> HandleRes {
+        if let Some(v) = self.sub_view.as_mut() {
+            let r = v.handle_event(event, state);
+            if let HandleRes::Exit(changed) = r {
+                self.sub_view = None;
+                if changed {
+                    self.reload(&mut state.backend);
+                }
+                return HandleRes::ReDraw;
+            }
+            return r;
+        }
+
+        match self.cur_prompt {
+            CurPrompt::Notice => {
+                let r = self.prompt.handle_event(event, &mut ());
+                if let HandleRes::Exit(_) = r {
+                    self.cur_prompt = CurPrompt::None;
+                    return HandleRes::ReDraw;
+                }
+                return r;
+            }
+            CurPrompt::Filter => {
+                let r = self.prompt.handle_event(event, &mut ());
+                if let HandleRes::Exit(apply) = r {
+                    self.cur_prompt = CurPrompt::None;
+                    if apply {
+                        self.filter.search_term =
+                            self.prompt.fields[0].1.as_str().trim().to_string();
+                        self.filter.public_only = self.prompt.fields[1].1.as_str() == "true";
+                        self.blocked_only = self.prompt.fields[2].1.as_str() == "true";
+                        self.reload(&mut state.backend);
+                    }
+                    return HandleRes::ReDraw;
+                }
+                return r;
+            }
+            CurPrompt::Block => {
+                let r = self.prompt.handle_event(event, &mut ());
+                if let HandleRes::Exit(apply) = r {
+                    if !apply {
+                        self.cur_prompt = CurPrompt::None;
+                        return HandleRes::ReDraw;
+                    }
+                    let room_id = self.prompt.fields[0].1.as_str().trim().to_string();
+                    let block = self.prompt.fields[1].1.as_str() == "true";
+                    if !room_id.starts_with('!') || !room_id.contains(':') {
+                        self.prompt.error = "Expected a room id like !abc:example.org".into();
+                        return HandleRes::ReDraw;
+                    }
+                    self.cur_prompt = CurPrompt::None;
+                    match state.backend.set_room_blocked(room_id.as_str(), block) {
+                        Ok(_) => {
+                            self.remember_blocked(room_id.as_str(), block);
+                            self.set_row_blocked(room_id.as_str(), block);
+                        }
+                        Err(e) => {
+                            self.prompt.error_notice(e.as_str());
+                            self.cur_prompt = CurPrompt::Notice;
+                        }
+                    }
+                    return HandleRes::ReDraw;
+                }
+                return r;
+            }
+            CurPrompt::EventContext => {
+                let r = self.prompt.handle_event(event, &mut ());
+                if let HandleRes::Exit(apply) = r {
+                    if !apply {
+                        self.cur_prompt = CurPrompt::None;
+                        return HandleRes::ReDraw;
+                    }
+                    let room_id = self.prompt.fields[0].1.as_str().trim().to_string();
+                    let event_id = self.prompt.fields[1].1.as_str().trim().to_string();
+                    if !room_id.starts_with('!') || !event_id.starts_with('$') {
+                        self.prompt.error = "Expected ids like !abc:example.org and $xyz".into();
+                        return HandleRes::ReDraw;
+                    }
+                    self.cur_prompt = CurPrompt::None;
+                    let view = EventContextView::new(room_id.as_str(), event_id.as_str());
+                    self.open_sub_view(Box::new(view), state);
+                    return HandleRes::ReDraw;
+                }
+                return r;
+            }
+            CurPrompt::None => {}
+        }
+
+        let key = match event {
+            Event::Key(k) => k,
+            _ => return HandleRes::Ignored,
+        };
+
+        // Enter opens the room unless it edits the focused cell
+        if let KeyCode::Enter = key.code {
+            let editable = matches!(self.room_table.cur_item(), Some(i) if i.is_editable());
+            if !self.room_table.editing && !editable {
+                if let Some(room_id) = self.cur_room_id() {
+                    self.open_room(room_id.as_str(), state);
+                }
+                return HandleRes::ReDraw;
+            }
+        }
+
+        let r = self.room_table.handle_event(key);
+        if let Some((x, y)) = self.room_table.take_committed() {
+            self.apply_edit(x, y, &mut state.backend);
+        }
+        if !matches!(r, HandleRes::Ignored) {
+            // Fetch more rooms when reaching the end of the list
+            if self.room_table.focus_y + 1 >= self.room_table.len() {
+                if let Err(e) = self.load_next_chunk(&mut state.backend) {
+                    self.prompt.error_notice(e.as_str());
+                    self.cur_prompt = CurPrompt::Notice;
+                    return HandleRes::ReDraw;
+                }
+            }
+            return r;
+        }
+
+        match key.code {
+            KeyCode::F(5) => {
+                self.reload(&mut state.backend);
+                HandleRes::ReDraw
+            }
+            KeyCode::Char('/') => {
+                self.prompt.clear();
+                self.prompt.msg.push_str(
+                    "Filter rooms\nOnly blocked checks the listed rooms one by one. Rooms unknown to the server\nare only listed if they were blocked from this tab since it was opened",
+                );
+                self.prompt.fields.push((
+                    "Name, alias or id contains".into(),
+                    Editable::string(self.filter.search_term.as_str()),
+                ));
+                self.prompt.fields.push((
+                    "Only published".into(),
+                    Editable::bool(self.filter.public_only),
+                ));
+                self.prompt
+                    .fields
+                    .push(("Only blocked".into(), Editable::bool(self.blocked_only)));
+                self.prompt.true_button.push_str("Apply");
+                self.prompt.false_button.push_str("Cancel");
+                self.cur_prompt = CurPrompt::Filter;
+                HandleRes::ReDraw
+            }
+            KeyCode::Char('p') => {
+                self.open_sub_view(Box::new(RoomDirectoryView::default()), state);
+                HandleRes::ReDraw
+            }
+            KeyCode::Char('b') => {
+                self.prompt.clear();
+                self.prompt.msg.push_str(
+                    "Block or unblock a room by id\nThe room does not need to be known to the server yet",
+                );
+                self.prompt.fields.push((
+                    "Room ID".into(),
+                    Editable::string(self.cur_room_id().unwrap_or_default().as_str()),
+                ));
+                self.prompt
+                    .fields
+                    .push(("Block".into(), Editable::bool(true)));
+                self.prompt.true_button.push_str("Apply");
+                self.prompt.false_button.push_str("Cancel");
+                self.cur_prompt = CurPrompt::Block;
+                HandleRes::ReDraw
+            }
+            KeyCode::Char('e') => {
+                self.prompt.clear();
+                self.prompt
+                    .msg
+                    .push_str("Show an event with the events around it and the room state");
+                self.prompt.fields.push((
+                    "Room ID".into(),
+                    Editable::string(self.cur_room_id().unwrap_or_default().as_str()),
+                ));
+                self.prompt
+                    .fields
+                    .push(("Event ID".into(), Editable::string("")));
+                self.prompt.true_button.push_str("Show");
+                self.prompt.false_button.push_str("Cancel");
+                self.cur_prompt = CurPrompt::EventContext;
+                HandleRes::ReDraw
+            }
+            _ => HandleRes::Ignored,
+        }
+    }
+
+    fn tick(&mut self, state: &mut State) -> HandleRes {
+        if let Some(v) = self.sub_view.as_mut() {
+            return v.tick(state);
+        }
+        if self.block_resolved >= self.room_table.len() {
+            // Keep scanning for blocked rooms until the end of the list
+            if !self.blocked_only || !matches!(self.sync_state, SyncState::Some) {
+                if self.blocked_only && self.add_blocked_here() {
+                    return HandleRes::ReDraw;
+                }
+                return HandleRes::Ignored;
+            }
+            if let Err(e) = self.load_next_chunk(&mut state.backend) {
+                self.blocked_only = false;
+                self.prompt.error_notice(e.as_str());
+                self.cur_prompt = CurPrompt::Notice;
+            }
+            return HandleRes::ReDraw;
+        }
+        for _ in 0..BLOCK_CHECKS_PER_TICK {
+            let y = self.block_resolved;
+            if y >= self.room_table.len() {
+                break;
+            }
+            let room_id = self.room_table.rows[y][0].as_str().to_string();
+            match state.backend.room_blocked(room_id.as_str()) {
+                Ok(false) if self.blocked_only => {
+                    self.room_table.remove(y);
+                    continue;
+                }
+                Ok(blocked) => self.room_table.rows[y][BLOCKED_COLUMN] = Editable::bool(blocked),
+                Err(_) => self.room_table.rows[y][BLOCKED_COLUMN] = Editable::ro_string("?"),
+            }
+            self.block_resolved += 1;
+        }
+        HandleRes::ReDraw
+    }
+
+    fn goto(&mut self, target: &Goto, state: &mut State) -> bool {
+        match target {
+            Goto::Room(room_id) => {
+                self.cur_prompt = CurPrompt::None;
+                self.open_room(room_id.as_str(), state);
+                true
+            }
+        }
+    }
+
+    fn hints(&self) -> Vec<(&'static str, &'static str)> {
+        if let Some(v) = self.sub_view.as_ref() {
+            return v.hints();
+        }
+        vec![
+            ("Enter", "Details/Toggle"),
+            ("b", "Block by id"),
+            ("p", "Room directory"),
+            ("e", "Event context"),
+            ("/", "Filter"),
+            ("F5", "Reload"),
+        ]
+    }
+}
+
+impl RoomsView {
+    fn open_sub_view(&mut self, mut view: Box<dyn ViewImpl<State>>, state: &mut State) {
+        view.enter_view(state);
+        self.sub_view = Some(view);
+    }
+
+    fn open_room(&mut self, room_id: &str, state: &mut State) {
+        self.open_sub_view(Box::new(RoomDetailView::new(room_id)), state);
+    }
+
+    /// Id of the room in the focused row
+    fn cur_room_id(&self) -> Option<String> {
+        self.room_table
+            .cur_row()
+            .map(|y| self.room_table.rows[y][0].as_str().to_string())
+    }
+
+    /// Clears the list and fetches the first chunk of rooms again
+    fn reload(&mut self, synapse: &mut Synapse) {
+        self.room_table.clear();
+        self.fetched = 0;
+        self.block_resolved = 0;
+        self.sync_state = SyncState::Some;
+        if let Err(e) = self.load_next_chunk(synapse) {
+            self.prompt.error_notice(e.as_str());
+            self.cur_prompt = CurPrompt::Notice;
+        }
+    }
+
+    fn load_next_chunk(&mut self, synapse: &mut Synapse) -> Result<usize, String> {
+        if let SyncState::Max = self.sync_state {
+            return Ok(0);
+        }
+        let mut l = synapse.list_rooms(self.fetched, PAGE_SIZE, &self.filter)?;
+        let num_received = l.len();
+        self.fetched += num_received;
+        for r in l.drain(..) {
+            self.room_table.push(room_row(&r));
+        }
+        // We got less than what we queried for, we hit the end
+        if num_received < PAGE_SIZE {
+            self.sync_state = SyncState::Max;
+        } else {
+            self.sync_state = SyncState::Some;
+        }
+        Ok(num_received)
+    }
+
+    /// Applies a value the user just modified in the table
+    fn apply_edit(&mut self, x: usize, y: usize, synapse: &mut Synapse) {
+        let room_id = self.room_table.rows[y][0].as_str().to_string();
+        let cell = &mut self.room_table.rows[y][x];
+        let value = cell.as_str() == "true";
+        let r = match x {
+            PUBLIC_COLUMN => synapse.set_room_published(room_id.as_str(), value),
+            BLOCKED_COLUMN => synapse.set_room_blocked(room_id.as_str(), value),
+            _ => return,
+        };
+        match r {
+            Ok(_) => {
+                cell.forget_orig();
+                if x == BLOCKED_COLUMN {
+                    self.remember_blocked(room_id.as_str(), value);
+                }
+            }
+            Err(e) => {
+                cell.restore_orig();
+                self.prompt.error_notice(e.as_str());
+                self.cur_prompt = CurPrompt::Notice;
+            }
+        }
+    }
+
+    /// Keeps track of the rooms blocked from this tab
+    fn remember_blocked(&mut self, room_id: &str, blocked: bool) {
+        self.blocked_here.retain(|r| r != room_id);
+        if blocked {
+            self.blocked_here.push(room_id.to_string());
+        }
+    }
+
+    /// Lists the rooms blocked from this tab that the scan did not find, as
+    /// rooms blocked before anyone joined are not known to the room list.
+    /// Returns whether any was added
+    fn add_blocked_here(&mut self) -> bool {
+        if self.filter.public_only {
+            return false;
+        }
+        let mut added = false;
+        for room_id in self.blocked_here.iter() {
+            if !room_id.contains(self.filter.search_term.as_str())
+                || (0..self.room_table.len())
+                    .any(|y| self.room_table.rows[y][0].as_str() == room_id)
+            {
+                continue;
+            }
+            self.room_table.push(vec![
+                Editable::ro_string(room_id.as_str()),
+                Editable::ro_string("(blocked from this tab)"),
+                Editable::ro_string(""),
+                Editable::ro_string(""),
+                Editable::ro_string(""),
+                Editable::ro_string(""),
+                Editable::bool(true),
+            ]);
+            added = true;
+        }
+        self.block_resolved = self.room_table.len();
+        added
+    }
+
+    /// Updates the row of a room blocked from the prompt, if listed
+    fn set_row_blocked(&mut self, room_id: &str, blocked: bool) {
+        let y =
+            (0..self.room_table.len()).find(|y| self.room_table.rows[*y][0].as_str() == room_id);
+        if let Some(y) = y {
+            if y < self.block_resolved {
+                self.room_table.rows[y][BLOCKED_COLUMN] = Editable::bool(blocked);
+            }
+        }
+    }
+
+    fn status_line(&self) -> String {
+        let mut r = format!(
+            "{}{} rooms",
+            self.room_table.len(),
+            if let SyncState::Some = self.sync_state {
+                "+"
+            } else {
+                ""
+            }
+        );
+        if !self.filter.search_term.is_empty() {
+            r.push_str(&format!(" | contains '{}'", self.filter.search_term));
+        }
+        if self.filter.public_only {
+            r.push_str(" | only published");
+        }
+        if self.blocked_only {
+            r.push_str(" | only blocked");
+            if matches!(self.sync_state, SyncState::Some)
+                || self.block_resolved < self.room_table.len()
+            {
+                r.push_str(", scanning...");
+            }
+        }
+        r
+    }
+}
+
+fn room_row(r: &RoomInfoV1) -> Vec<Editable> {
+    vec![
+        Editable::ro_string(r.room_id.as_str()),
+        Editable::ro_string(r.name.as_deref().unwrap_or("")),
+        Editable::ro_string(r.canonical_alias.as_deref().unwrap_or("")),
+        Editable::ro_string(r.joined_members.to_string().as_str()),
+        Editable::ro_string(r.joined_local_members.to_string().as_str()),
+        Editable::bool(r.public),
+        // Fetched in the background
+        Editable::ro_string(""),
+    ]
+}
@@ -0,0 +1,325 @@
+use crossterm::event::{Event, KeyCode};
+use tui::{
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph, Wrap},
+};
+
+use crate::{
+    backend::{NoticeContentV1, UserFilter},
+    common::{
+        batch::Batch,
+        editable::{Editable, EditableWidget},
+        prompt::Prompt,
+        HandleRes, ViewImpl,
+    },
+    state::State,
+};
+
+#[derive(Default)]
+enum CurPrompt {
+    #[default]
+    None,
+    Notice,
+    Compose,
+    Preview,
+}
+
+/// Users receiving the notice
+pub enum NoticeTargets {
+    Users(Vec<String>),
+    /// Every active local user, listed when sending
+    AllLocal,
+}
+
+/// Background work once the notice is confirmed
+enum Sending {
+    /// Listing the local users, with the ones listed so far
+    Listing(Vec<String>),
+    Sending(Batch<String>),
+}
+
+/// Number of recipients listed in the preview
+const MAX_LISTED_USERS: usize = 10;
+/// Users listed per tick when sending to everyone
+const PAGE_SIZE: usize = 100;
+
+/// Composes and sends a message from the server notices account
+pub struct ServerNoticeView {
+    cur_prompt: CurPrompt,
+    prompt: Prompt,
+    targets: NoticeTargets,
+    body: String,
+    html: String,
+    sending: Option<Sending>,
+}
+
+impl ServerNoticeView {
+    pub fn new(targets: NoticeTargets) -> Self {
+        Self {
+            cur_prompt: CurPrompt::default(),
+            prompt: Prompt::default(),
+            targets,
+            body: String::new(),
+            html: String::new(),
+            sending: None,
+        }
+    }
+
+    fn recipients_desc(&self) -> String {
+        match &self.targets {
+            NoticeTargets::Users(users) if users.len() == 1 => users[0].clone(),
+            NoticeTargets::Users(users) => format!("{} users", users.len()),
+            NoticeTargets::AllLocal => "every local user".into(),
+        }
+    }
+
+    fn compose(&mut self) {
+        self.prompt.clear();
+        self.prompt.msg = format!(
+            "Server notice to {}\nThe formatted body is optional HTML\nEnter adds a line, Ctrl+Enter (or Ctrl+J) finishes",
+            self.recipients_desc()
+        );
+        self.prompt
+            .fields
+            .push(("Message".into(), Editable::text(self.body.as_str())));
+        self.prompt
+            .fields
+            .push(("Formatted body".into(), Editable::text(self.html.as_str())));
+        self.prompt.true_button.push_str("Preview");
+        self.prompt.false_button.push_str("Cancel");
+        self.cur_prompt = CurPrompt::Compose;
+    }
+
+    fn preview(&mut self) {
+        self.prompt.clear();
+        self.prompt.msg = format!("Send this notice to {} ?\n", self.recipients_desc());
+        if let NoticeTargets::Users(users) = &self.targets {
+            if users.len() > 1 {
+                for u in users.iter().take(MAX_LISTED_USERS) {
+                    self.prompt.msg.push_str("  ");
+                    self.prompt.msg.push_str(u);
+                    self.prompt.msg.push('\n');
+                }
+                if users.len() > MAX_LISTED_USERS {
+                    self.prompt.msg.push_str("  ...\n");
+                }
+            }
+        }
+        self.prompt.msg.push_str("\n──── Message ────\n");
+        self.prompt.msg.push_str(self.body.as_str());
+        if !self.html.is_empty() {
+            self.prompt.msg.push_str("\n──── Formatted body ────\n");
+            self.prompt.msg.push_str(self.html.as_str());
+        }
+        self.prompt.true_button.push_str("Send");
+        self.prompt.false_button.push_str("Edit");
+        self.cur_prompt = CurPrompt::Preview;
+    }
+
+    fn start_sending(&mut self) {
+        self.sending = Some(match &self.targets {
+            NoticeTargets::Users(users) => Sending::Sending(Batch::new(users.clone())),
+            NoticeTargets::AllLocal => Sending::Listing(Vec::new()),
+        });
+    }
+
+    /// Displays the results of the sending, complete or not
+    fn finish(&mut self) {
+        let batch = match self.sending.take() {
+            Some(Sending::Sending(b)) => b,
+            Some(Sending::Listing(users)) => {
+                self.prompt
+                    .notice(&format!("Stopped after listing {} users", users.len()));
+                self.cur_prompt = CurPrompt::Notice;
+                return;
+            }
+            None => return,
+        };
+        self.prompt.clear();
+        self.prompt.msg = format!("Server notice : {}", batch.progress());
+        if !batch.is_done() {
+            self.prompt.msg.push_str(" (stopped)");
+        }
+        for (idx, e) in batch.failures.iter() {
+            self.prompt
+                .error
+                .push_str(&format!("{} : {}\n", batch.items[*idx], e));
+        }
+        self.prompt.true_button.push_str("Ok");
+        self.cur_prompt = CurPrompt::Notice;
+    }
+
+    fn content(&self) -> NoticeContentV1 {
+        NoticeContentV1::new(
+            self.body.as_str(),
+            if self.html.is_empty() {
+                None
+            } else {
+                Some(self.html.as_str())
+            },
+        )
+    }
+}
+
+impl ViewImpl<State> for ServerNoticeView {
+    fn title(&self) -> &'static str {
+        "Server notice"
+    }
+
+    fn enter_view(&mut self, _state: &mut State) {
+        self.compose();
+    }
+
+    fn draw_view(
+        &mut self,
+        frame: &mut tui::Frame<tui::backend::CrosstermBackend<&mut std::io::Stdout>>,
+        rect: tui::layout::Rect,
+        _state: &mut State,
+    ) {
+        if !matches!(self.cur_prompt, CurPrompt::None) {
+            self.prompt.draw_view(frame, rect, &mut ());
+            return;
+        }
+        let status = match self.sending.as_ref() {
+            Some(Sending::Listing(users)) => format!("Listing local users : {}", users.len()),
+            Some(Sending::Sending(batch)) => format!("Sending : {}", batch.progress()),
+            None => String::new(),
+        };
+        let rects = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(0)])
+            .split(rect);
+        frame.render_widget(
+            Paragraph::new(Span::styled(status, Style::default().fg(Color::Yellow))),
+            rects[0],
+        );
+        let mut lines: Vec<Spans> = self
+            .body
+            .lines()
+            .map(|l| Spans::from(l.to_string()))
+            .collect();
+        if let Some(Sending::Sending(batch)) = self.sending.as_ref() {
+            if !batch.failures.is_empty() {
+                lines.push(Spans::from(""));
+            }
+            for (idx, e) in batch.failures.iter() {
+                lines.push(Spans::from(Span::styled(
+                    format!("{} : {}", batch.items[*idx], e),
+                    Style::default().fg(Color::Red),
+                )));
+            }
+        }
+        frame.render_widget(
+            Paragraph::new(lines).wrap(Wrap { trim: false }).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!("Server notice to {}", self.recipients_desc())),
+            ),
+            rects[1],
+        );
+    }
+
+    fn handle_event(&mut self, event: &Event, _state: &mut State) -> HandleRes {
+        match self.cur_prompt {
+            CurPrompt::Notice => {
+                let r = self.prompt.handle_event(event, &mut ());
+                if let HandleRes::Exit(_) = r {
+                    self.cur_prompt = CurPrompt::None;
+                    return HandleRes::Exit(false);
+                }
+                return r;
+            }
+            CurPrompt::Compose => {
+                let r = self.prompt.handle_event(event, &mut ());
+                if let HandleRes::Exit(confirmed) = r {
+                    if !confirmed {
+                        self.cur_prompt = CurPrompt::None;
+                        return HandleRes::Exit(false);
+                    }
+                    self.body = self.prompt.fields[0].1.as_str().trim().to_string();
+                    self.html = self.prompt.fields[1].1.as_str().trim().to_string();
+                    if self.body.is_empty() {
+                        self.prompt.error = "The message cannot be empty".into();
+                    } else {
+                        self.preview();
+                    }
+                    return HandleRes::ReDraw;
+                }
+                return r;
+            }
+            CurPrompt::Preview => {
+                let r = self.prompt.handle_event(event, &mut ());
+                if let HandleRes::Exit(confirmed) = r {
+                    if confirmed {
+                        self.cur_prompt = CurPrompt::None;
+                        self.start_sending();
+                    } else {
+                        self.compose();
+                    }
+                    return HandleRes::ReDraw;
+                }
+                return r;
+            }
+            CurPrompt::None => {}
+        }
+
+        match event {
+            Event::Key(k) if k.code == KeyCode::Esc => {
+                self.finish();
+                HandleRes::ReDraw
+            }
+            _ => HandleRes::Ignored,
+        }
+    }
+
+    fn tick(&mut self, state: &mut State) -> HandleRes {
+        let content = self.content();
+        match self.sending.as_mut() {
+            Some(Sending::Listing(users)) => {
+                let page =
+                    match state
+                        .backend
+                        .list_users(users.len(), PAGE_SIZE, &UserFilter::default())
+                    {
+                        Ok(p) => p,
+                        Err(e) => {
+                            self.sending = None;
+                            self.prompt.error_notice(e.as_str());
+                            self.cur_prompt = CurPrompt::Notice;
+                            return HandleRes::ReDraw;
+                        }
+                    };
+                let hit_end = page.len() < PAGE_SIZE;
+                users.extend(page.into_iter().map(|u| u.name));
+                if hit_end {
+                    let users = std::mem::take(users);
+                    self.sending = Some(Sending::Sending(Batch::new(users)));
+                }
+                HandleRes::ReDraw
+            }
+            Some(Sending::Sending(batch)) => {
+                batch.step(|user_id| {
+                    state
+                        .backend
+                        .send_server_notice(user_id.as_str(), &content)
+                        .map(|_| ())
+                });
+                if batch.is_done() {
+                    self.finish();
+                }
+                HandleRes::ReDraw
+            }
+            None => HandleRes::Ignored,
+        }
+    }
+
+    fn hints(&self) -> Vec<(&'static str, &'static str)> {
+        if self.sending.is_some() {
+            vec![("Esc", "Stop")]
+        } else {
+            Vec::new()
+        }
+    }
+}
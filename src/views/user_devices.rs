@@ -0,0 +1,209 @@
+use crossterm::event::{Event, KeyCode};
+use tui::widgets::{Block, Borders};
+
+use crate::{
+    backend::DeviceInfoV2,
+    common::{
+        editable::{Editable, EditableWidget},
+        human::format_opt_ts,
+        prompt::Prompt,
+        table::EditTable,
+        HandleRes, ViewImpl,
+    },
+    state::State,
+};
+
+#[derive(Default)]
+enum CurPrompt {
+    #[default]
+    None,
+    Notice,
+    ConfirmDelete,
+}
+
+const DEVICE_COLUMNS: [&str; 5] = ["Device", "Name", "Last seen IP", "User agent", "Last seen"];
+const NAME_COLUMN: usize = 1;
+
+/// Lists the devices of a user
+pub struct UserDevicesView {
+    user_id: String,
+    cur_prompt: CurPrompt,
+    prompt: Prompt,
+    devices: Vec<DeviceInfoV2>,
+    table: EditTable,
+    /// Rows to delete once confirmed
+    to_delete: Vec<usize>,
+}
+
+impl UserDevicesView {
+    pub fn new(user_id: &str) -> Self {
+        Self {
+            user_id: user_id.to_string(),
+            cur_prompt: CurPrompt::default(),
+            prompt: Prompt::default(),
+            devices: Vec::new(),
+            table: EditTable::new(&DEVICE_COLUMNS).with_selection(),
+            to_delete: Vec::new(),
+        }
+    }
+
+    fn reload(&mut self, state: &mut State) {
+        self.table.clear();
+        self.devices = match state.backend.list_devices(self.user_id.as_str()) {
+            Ok(d) => d,
+            Err(e) => {
+                self.prompt.error_notice(e.as_str());
+                self.cur_prompt = CurPrompt::Notice;
+                return;
+            }
+        };
+        for d in self.devices.iter() {
+            self.table.push(vec![
+                Editable::ro_string(d.device_id.as_str()),
+                Editable::string(d.display_name.as_deref().unwrap_or("")),
+                Editable::ro_string(d.last_seen_ip.as_deref().unwrap_or("-")),
+                Editable::ro_string(d.last_seen_user_agent.as_deref().unwrap_or("-")),
+                Editable::ro_string(format_opt_ts(d.last_seen_ts).as_str()),
+            ]);
+        }
+    }
+
+    fn delete_devices(&mut self, state: &mut State) {
+        let ids: Vec<String> = self
+            .to_delete
+            .drain(..)
+            .map(|y| self.devices[y].device_id.clone())
+            .collect();
+        let r = if ids.len() == 1 {
+            state.backend.delete_device(self.user_id.as_str(), &ids[0])
+        } else {
+            state.backend.delete_devices(self.user_id.as_str(), &ids)
+        };
+        if let Err(e) = r {
+            self.prompt.error_notice(e.as_str());
+            self.cur_prompt = CurPrompt::Notice;
+        }
+        self.reload(state);
+    }
+}
+
+impl ViewImpl<State> for UserDevicesView {
+    fn title(&self) -> &'static str {
+        "Devices"
+    }
+
+    fn enter_view(&mut self, state: &mut State) {
+        self.reload(state);
+    }
+
+    fn draw_view(
+        &mut self,
+        frame: &mut tui::Frame<tui::backend::CrosstermBackend<&mut std::io::Stdout>>,
+        rect: tui::layout::Rect,
+        _state: &mut State,
+    ) {
+        if !matches!(self.cur_prompt, CurPrompt::None) {
+            self.prompt.draw_view(frame, rect, &mut ());
+            return;
+        }
+        let title = format!("Devices of {} ({})", self.user_id, self.devices.len());
+        self.table.draw(
+            frame,
+            rect,
+            Block::default().borders(Borders::ALL).title(title),
+        );
+    }
+
+    fn handle_event(&mut self, event: &Event, state: &mut State) -> HandleRes {
+        match self.cur_prompt {
+            CurPrompt::Notice => {
+                let r = self.prompt.handle_event(event, &mut ());
+                if let HandleRes::Exit(_) = r {
+                    self.cur_prompt = CurPrompt::None;
+                    return HandleRes::ReDraw;
+                }
+                return r;
+            }
+            CurPrompt::ConfirmDelete => {
+                let r = self.prompt.handle_event(event, &mut ());
+                if let HandleRes::Exit(confirmed) = r {
+                    self.cur_prompt = CurPrompt::None;
+                    if confirmed {
+                        self.delete_devices(state);
+                    }
+                    return HandleRes::ReDraw;
+                }
+                return r;
+            }
+            CurPrompt::None => {}
+        }
+
+        let key = match event {
+            Event::Key(k) => k,
+            _ => return HandleRes::Ignored,
+        };
+
+        let r = self.table.handle_event(key);
+        if let Some((x, y)) = self.table.take_committed() {
+            if x == NAME_COLUMN {
+                let cell = &mut self.table.rows[y][x];
+                match state.backend.rename_device(
+                    self.user_id.as_str(),
+                    self.devices[y].device_id.as_str(),
+                    cell.as_str(),
+                ) {
+                    Ok(_) => cell.forget_orig(),
+                    Err(e) => {
+                        cell.restore_orig();
+                        self.prompt.error_notice(e.as_str());
+                        self.cur_prompt = CurPrompt::Notice;
+                    }
+                }
+            }
+        }
+        if !matches!(r, HandleRes::Ignored) {
+            return r;
+        }
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => HandleRes::Exit(false),
+            KeyCode::F(5) => {
+                self.reload(state);
+                HandleRes::ReDraw
+            }
+            KeyCode::Delete | KeyCode::Char('d') => {
+                self.to_delete = self.table.selection();
+                if self.to_delete.is_empty() {
+                    return HandleRes::Handled;
+                }
+                self.prompt.clear();
+                self.prompt.msg = format!(
+                    "Delete {} device(s) of {} ? The sessions will be logged out",
+                    self.to_delete.len(),
+                    self.user_id
+                );
+                for y in self.to_delete.iter() {
+                    self.prompt.msg.push_str("\n  ");
+                    self.prompt
+                        .msg
+                        .push_str(self.devices[*y].device_id.as_str());
+                }
+                self.prompt.true_button.push_str("Delete");
+                self.prompt.false_button.push_str("Cancel");
+                self.cur_prompt = CurPrompt::ConfirmDelete;
+                HandleRes::ReDraw
+            }
+            _ => HandleRes::Ignored,
+        }
+    }
+
+    fn hints(&self) -> Vec<(&'static str, &'static str)> {
+        vec![
+            ("Enter", "Rename"),
+            ("Space", "Select"),
+            ("d/Del", "Delete"),
+            ("F5", "Reload"),
+            ("Esc", "Back"),
+        ]
+    }
+}
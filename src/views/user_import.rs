@@ -0,0 +1,727 @@
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+use std::{
+    fs::{File, OpenOptions},
+    io::Write,
+};
+
+use crossterm::event::{Event, KeyCode};
+use rand::{distributions::Alphanumeric, Rng};
+use serde::Deserialize;
+use tui::{
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Style},
+    text::Span,
+    widgets::{Block, Borders, Paragraph},
+};
+
+use crate::{
+    backend::{ThreepidV2, UserCreateV2},
+    common::{
+        batch::Batch,
+        editable::{Editable, EditableWidget},
+        prompt::Prompt,
+        table::EditTable,
+        HandleRes, ViewImpl,
+    },
+    state::State,
+};
+
+const IMPORT_COLUMNS: [&str; 6] = ["Localpart", "Name", "Email", "Admin", "Password", "Status"];
+const STATUS_COLUMN: usize = IMPORT_COLUMNS.len() - 1;
+const GENERATED_PASSWORD_LEN: usize = 16;
+
+#[derive(Default)]
+enum CurPrompt {
+    None,
+    #[default]
+    File,
+    Confirm,
+    Notice,
+}
+
+#[derive(Default)]
+enum Stage {
+    /// Waiting for the user to pick a file
+    #[default]
+    Loading,
+    /// Looking up which users already exist
+    Checking,
+    /// Dry run results are displayed
+    Preview,
+    Creating,
+    Done,
+}
+
+enum RowStatus {
+    Invalid(String),
+    Checking,
+    Exists,
+    Ready,
+    Created,
+    /// The user was created but its generated password could not be written
+    PasswordNotSaved(String),
+    Failed(String),
+}
+impl RowStatus {
+    fn as_str(&self) -> &str {
+        match self {
+            Self::Invalid(e) | Self::PasswordNotSaved(e) | Self::Failed(e) => e.as_str(),
+            Self::Checking => "Checking...",
+            Self::Exists => "Already exists",
+            Self::Ready => "Ready",
+            Self::Created => "Created",
+        }
+    }
+}
+
+/// One user entry from the import file
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct ImportRow {
+    localpart: String,
+    displayname: String,
+    email: String,
+    admin: serde_json::Value,
+    password: String,
+    #[serde(skip)]
+    is_admin: bool,
+    #[serde(skip)]
+    generated: bool,
+    #[serde(skip)]
+    status: Option<RowStatus>,
+}
+
+/// Creates users in bulk from a csv or json file
+#[derive(Default)]
+pub struct UserImportView {
+    cur_prompt: CurPrompt,
+    prompt: Prompt,
+    stage: Stage,
+    rows: Vec<ImportRow>,
+    table: EditTable,
+    batch: Option<Batch<usize>>,
+    output_path: String,
+    output: Option<File>,
+    /// Whether any user was created
+    changed: bool,
+}
+
+impl ViewImpl<State> for UserImportView {
+    fn title(&self) -> &'static str {
+        "Import users"
+    }
+
+    fn draw_view(
+        &mut self,
+        frame: &mut tui::Frame<tui::backend::CrosstermBackend<&mut std::io::Stdout>>,
+        rect: tui::layout::Rect,
+        _state: &mut State,
+    ) {
+        if !matches!(self.cur_prompt, CurPrompt::None) {
+            self.prompt.draw_view(frame, rect, &mut ());
+            return;
+        }
+
+        let rects = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(0)])
+            .split(rect);
+
+        frame.render_widget(
+            Paragraph::new(Span::styled(
+                self.status_line(),
+                Style::default().fg(Color::Yellow),
+            )),
+            rects[0],
+        );
+        self.table.draw(
+            frame,
+            rects[1],
+            Block::default().borders(Borders::LEFT | Borders::RIGHT),
+        );
+    }
+
+    fn handle_event(&mut self, event: &Event, _state: &mut State) -> HandleRes {
+        match self.cur_prompt {
+            CurPrompt::File => {
+                let r = self.prompt.handle_event(event, &mut ());
+                match r {
+                    HandleRes::Exit(true) => {
+                        if let Err(e) = self.load_file() {
+                            self.prompt.error.clear();
+                            self.prompt.error.push_str(e.as_str());
+                            return HandleRes::ReDraw;
+                        }
+                        self.start_checking();
+                        self.cur_prompt = CurPrompt::None;
+                        return HandleRes::ReDraw;
+                    }
+                    HandleRes::Exit(false) => return HandleRes::Exit(false),
+                    _ => return r,
+                }
+            }
+            CurPrompt::Confirm => {
+                let r = self.prompt.handle_event(event, &mut ());
+                match r {
+                    HandleRes::Exit(confirmed) => {
+                        self.cur_prompt = CurPrompt::None;
+                        if confirmed {
+                            if let Err(e) = self.start_creating() {
+                                self.prompt.error_notice(e.as_str());
+                                self.cur_prompt = CurPrompt::Notice;
+                            }
+                        }
+                        return HandleRes::ReDraw;
+                    }
+                    _ => return r,
+                }
+            }
+            CurPrompt::Notice => {
+                let r = self.prompt.handle_event(event, &mut ());
+                if let HandleRes::Exit(_) = r {
+                    self.cur_prompt = CurPrompt::None;
+                    return HandleRes::ReDraw;
+                }
+                return r;
+            }
+            CurPrompt::None => {}
+        }
+
+        let key = match event {
+            Event::Key(k) => k,
+            _ => return HandleRes::Ignored,
+        };
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                if let Stage::Creating = self.stage {
+                    // Stop creating users but keep the results on screen
+                    self.finish();
+                    return HandleRes::ReDraw;
+                }
+                return HandleRes::Exit(self.changed);
+            }
+            KeyCode::Enter if matches!(self.stage, Stage::Preview) => {
+                let num_ready = self.count(|s| matches!(s, RowStatus::Ready));
+                if num_ready == 0 {
+                    self.prompt.notice("There are no users ready to be created");
+                    self.cur_prompt = CurPrompt::Notice;
+                    return HandleRes::ReDraw;
+                }
+                self.prompt.clear();
+                self.prompt.msg = format!(
+                    "Create {} users ?\nGenerated passwords will be written to {}",
+                    num_ready, self.output_path
+                );
+                self.prompt.true_button.push_str("Create");
+                self.prompt.false_button.push_str("Cancel");
+                self.cur_prompt = CurPrompt::Confirm;
+                return HandleRes::ReDraw;
+            }
+            _ => {}
+        }
+
+        self.table.handle_event(key)
+    }
+
+    fn tick(&mut self, state: &mut State) -> HandleRes {
+        let batch = match self.batch.as_mut() {
+            Some(b) => b,
+            None => return HandleRes::Ignored,
+        };
+        let rows = &mut self.rows;
+        let output = &mut self.output;
+        let synapse = &mut state.backend;
+        let mut changed_row = None;
+        let did_work = match self.stage {
+            Stage::Checking => batch.step(|idx| {
+                let row = &mut rows[*idx];
+                changed_row = Some(*idx);
+                let status = match synapse.username_available(row.localpart.as_str()) {
+                    Ok(true) => RowStatus::Ready,
+                    Ok(false) => RowStatus::Exists,
+                    Err(e) => RowStatus::Invalid(e),
+                };
+                row.status = Some(status);
+                Ok(())
+            }),
+            Stage::Creating => batch.step(|idx| {
+                let row = &mut rows[*idx];
+                changed_row = Some(*idx);
+                let user_id = synapse.user_id(row.localpart.as_str());
+                let mut req = UserCreateV2 {
+                    password: Some(row.password.clone()),
+                    admin: row.is_admin,
+                    ..Default::default()
+                };
+                if !row.displayname.is_empty() {
+                    req.displayname = Some(row.displayname.clone());
+                }
+                if !row.email.is_empty() {
+                    req.threepids.push(ThreepidV2 {
+                        medium: "email".into(),
+                        address: row.email.clone(),
+                    });
+                }
+                if let Err(e) = synapse.create_user(user_id.as_str(), &req) {
+                    row.status = Some(RowStatus::Failed(e.clone()));
+                    return Err(e);
+                }
+                row.status = Some(RowStatus::Created);
+                if row.generated {
+                    if let Some(f) = output.as_mut() {
+                        if let Err(e) = writeln!(f, "{},{}", user_id, row.password) {
+                            let e = format!("Created, password not saved : {}", e);
+                            row.status = Some(RowStatus::PasswordNotSaved(e));
+                        }
+                    }
+                }
+                Ok(())
+            }),
+            _ => false,
+        };
+
+        let done = !did_work || batch.is_done();
+        if let Some(idx) = changed_row {
+            self.refresh_status(idx);
+        }
+        if done {
+            self.finish();
+        }
+        HandleRes::ReDraw
+    }
+
+    fn hints(&self) -> Vec<(&'static str, &'static str)> {
+        match self.stage {
+            Stage::Preview => vec![("Enter", "Create users"), ("Esc", "Cancel")],
+            Stage::Creating => vec![("Esc", "Stop")],
+            _ => vec![("Esc", "Close")],
+        }
+    }
+}
+
+impl UserImportView {
+    pub fn new() -> Self {
+        let mut r = Self::default();
+        r.set_file_prompt();
+        r
+    }
+
+    fn set_file_prompt(&mut self) {
+        self.prompt.clear();
+        self.prompt.msg.push_str(
+            "Import users from a csv or json file\n\
+             Columns : localpart, displayname, email, admin, password\n\
+             Users without a password get a generated one",
+        );
+        self.prompt
+            .fields
+            .push(("File".into(), Editable::string("")));
+        self.prompt
+            .fields
+            .push(("Passwords output".into(), Editable::string("")));
+        self.prompt.true_button.push_str("Load");
+        self.prompt.false_button.push_str("Cancel");
+    }
+
+    /// Parses the file selected in the prompt and validates its rows
+    fn load_file(&mut self) -> Result<(), String> {
+        let path = self.prompt.fields[0].1.as_str().trim().to_string();
+        if path.is_empty() {
+            return Err("A file is required".into());
+        }
+        let contents = match std::fs::read_to_string(&path) {
+            Ok(c) => c,
+            Err(e) => return Err(format!("Failed to read {} : {}", path, e)),
+        };
+        let mut rows = parse_rows(&path, &contents)?;
+        if rows.is_empty() {
+            return Err(format!("No users found in {}", path));
+        }
+
+        for i in 0..rows.len() {
+            let (previous, rest) = rows.split_at_mut(i);
+            let row = &mut rest[0];
+            let err = validate_row(previous, row);
+            if row.password.is_empty() {
+                row.generated = true;
+                row.password = rand::thread_rng()
+                    .sample_iter(&Alphanumeric)
+                    .take(GENERATED_PASSWORD_LEN)
+                    .map(char::from)
+                    .collect();
+            }
+            row.status = Some(match err {
+                Some(e) => RowStatus::Invalid(e),
+                None => RowStatus::Checking,
+            });
+        }
+
+        self.output_path = self.prompt.fields[1].1.as_str().trim().to_string();
+        if self.output_path.is_empty() {
+            self.output_path = format!("{}.passwords.csv", path);
+        }
+
+        self.table = EditTable::new(&IMPORT_COLUMNS);
+        for r in rows.iter() {
+            self.table.push(vec![
+                Editable::ro_string(r.localpart.as_str()),
+                Editable::ro_string(r.displayname.as_str()),
+                Editable::ro_string(r.email.as_str()),
+                Editable::ro_bool(r.is_admin),
+                Editable::ro_string(if r.generated {
+                    "(generated)"
+                } else {
+                    "********"
+                }),
+                Editable::ro_string(""),
+            ]);
+        }
+        self.rows = rows;
+        for idx in 0..self.rows.len() {
+            self.refresh_status(idx);
+        }
+        Ok(())
+    }
+
+    fn start_checking(&mut self) {
+        let to_check = (0..self.rows.len())
+            .filter(|i| matches!(self.rows[*i].status, Some(RowStatus::Checking)))
+            .collect();
+        self.batch = Some(Batch::new(to_check));
+        self.stage = Stage::Checking;
+    }
+
+    fn start_creating(&mut self) -> Result<(), String> {
+        let to_create: Vec<usize> = (0..self.rows.len())
+            .filter(|i| matches!(self.rows[*i].status, Some(RowStatus::Ready)))
+            .collect();
+        if to_create.iter().any(|i| self.rows[*i].generated) {
+            // The file holds plaintext passwords, keep it private and never overwrite one
+            let mut options = OpenOptions::new();
+            options.write(true).create_new(true);
+            #[cfg(unix)]
+            options.mode(0o600);
+            let mut f = match options.open(&self.output_path) {
+                Ok(f) => f,
+                Err(e) => return Err(format!("Failed to create {} : {}", self.output_path, e)),
+            };
+            if let Err(e) = writeln!(f, "user_id,password") {
+                return Err(format!("Failed to write {} : {}", self.output_path, e));
+            }
+            self.output = Some(f);
+        }
+        self.batch = Some(Batch::new(to_create));
+        self.stage = Stage::Creating;
+        Ok(())
+    }
+
+    /// Called once the current batch is complete or aborted
+    fn finish(&mut self) {
+        let batch = match self.batch.take() {
+            Some(b) => b,
+            None => return,
+        };
+        match self.stage {
+            Stage::Checking => self.stage = Stage::Preview,
+            Stage::Creating => {
+                self.stage = Stage::Done;
+                self.output = None;
+                self.changed |= batch.succeeded() > 0;
+                let mut msg = format!(
+                    "Created {} of {} users, {} failed",
+                    batch.succeeded(),
+                    batch.items.len(),
+                    batch.failures.len()
+                );
+                if !batch.is_done() {
+                    msg.push_str(" (stopped)");
+                }
+                let unsaved = self.count(|s| matches!(s, RowStatus::PasswordNotSaved(_)));
+                if unsaved > 0 {
+                    msg.push_str(&format!(
+                        "\n{} users were created but their password could not be saved",
+                        unsaved
+                    ));
+                }
+                if self.rows.iter().any(|r| r.generated) {
+                    msg.push_str(&format!(
+                        "\nGenerated passwords saved to {}",
+                        self.output_path
+                    ));
+                }
+                self.prompt.notice(msg.as_str());
+                self.cur_prompt = CurPrompt::Notice;
+            }
+            _ => {}
+        }
+    }
+
+    fn refresh_status(&mut self, idx: usize) {
+        let status = self.rows[idx]
+            .status
+            .as_ref()
+            .map(|s| s.as_str())
+            .unwrap_or("");
+        self.table.rows[idx][STATUS_COLUMN] = Editable::ro_string(status);
+    }
+
+    fn count<F: Fn(&RowStatus) -> bool>(&self, f: F) -> usize {
+        self.rows
+            .iter()
+            .filter(|r| r.status.as_ref().map(&f).unwrap_or(false))
+            .count()
+    }
+
+    fn status_line(&self) -> String {
+        match self.stage {
+            Stage::Checking | Stage::Creating => match self.batch.as_ref() {
+                Some(b) => b.progress(),
+                None => String::new(),
+            },
+            _ => format!(
+                "{} rows : {} ready, {} existing, {} invalid, {} created, {} failed",
+                self.rows.len(),
+                self.count(|s| matches!(s, RowStatus::Ready)),
+                self.count(|s| matches!(s, RowStatus::Exists)),
+                self.count(|s| matches!(s, RowStatus::Invalid(_))),
+                self.count(|s| matches!(s, RowStatus::Created | RowStatus::PasswordNotSaved(_))),
+                self.count(|s| matches!(s, RowStatus::Failed(_))),
+            ),
+        }
+    }
+}
+
+/// Returns the reason why `row` cannot be created
+fn validate_row(previous: &[ImportRow], row: &mut ImportRow) -> Option<String> {
+    row.localpart = row.localpart.trim().to_string();
+    row.email = row.email.trim().to_string();
+
+    row.is_admin = match &row.admin {
+        serde_json::Value::Null => false,
+        serde_json::Value::Bool(b) => *b,
+        serde_json::Value::Number(n) => n.as_u64() != Some(0),
+        serde_json::Value::String(s) => match s.trim().to_lowercase().as_str() {
+            "" | "false" | "no" | "0" => false,
+            "true" | "yes" | "1" => true,
+            _ => return Some(format!("Invalid admin value '{}'", s)),
+        },
+        _ => return Some("Invalid admin value".into()),
+    };
+
+    if row.localpart.is_empty() {
+        return Some("Missing localpart".into());
+    }
+    if !row
+        .localpart
+        .chars()
+        .all(|c| matches!(c, 'a'..='z' | '0'..='9' | '.' | '_' | '=' | '-' | '/' | '+'))
+    {
+        return Some("Invalid characters in localpart".into());
+    }
+    if let Some(idx) = previous.iter().position(|r| r.localpart == row.localpart) {
+        return Some(format!("Duplicate of row {}", idx + 1));
+    }
+    if !row.email.is_empty() && !row.email.contains('@') {
+        return Some("Invalid email".into());
+    }
+    None
+}
+
+/// Parses the rows of a json array, or of a csv file otherwise
+fn parse_rows(path: &str, contents: &str) -> Result<Vec<ImportRow>, String> {
+    if path.ends_with(".json") || contents.trim_start().starts_with('[') {
+        serde_json::from_str(contents).map_err(|e| format!("Invalid json : {}", e))
+    } else {
+        Ok(parse_csv(contents))
+    }
+}
+
+/// Parses csv rows. A header is used if the first line names the columns
+fn parse_csv(contents: &str) -> Vec<ImportRow> {
+    let mut columns = vec!["localpart", "displayname", "email", "admin", "password"];
+    let mut lines = contents.lines().filter(|l| !l.trim().is_empty()).peekable();
+
+    let header: Vec<String>;
+    let first = lines.peek().map(|l| parse_csv_line(l));
+    if let Some(fields) = first {
+        if fields
+            .iter()
+            .any(|f| f.trim().eq_ignore_ascii_case("localpart"))
+        {
+            header = fields.iter().map(|f| f.trim().to_lowercase()).collect();
+            columns = header.iter().map(|h| h.as_str()).collect();
+            lines.next();
+        }
+    }
+
+    let mut rows = Vec::new();
+    for line in lines {
+        let mut row = ImportRow::default();
+        for (col, val) in columns.iter().zip(parse_csv_line(line)) {
+            match *col {
+                "localpart" => row.localpart = val,
+                "displayname" => row.displayname = val,
+                "email" => row.email = val,
+                "admin" => row.admin = serde_json::Value::String(val),
+                "password" => row.password = val,
+                _ => {}
+            }
+        }
+        rows.push(row);
+    }
+    rows
+}
+
+/// Splits a csv line into its fields, handling quoted values
+fn parse_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut cur = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                cur.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut cur)),
+            _ => cur.push(c),
+        }
+    }
+    fields.push(cur);
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(localpart: &str, admin: serde_json::Value) -> ImportRow {
+        ImportRow {
+            localpart: localpart.into(),
+            admin,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn csv_lines() {
+        assert_eq!(parse_csv_line("a,b,,c"), ["a", "b", "", "c"]);
+        assert_eq!(
+            parse_csv_line(r#"alice,"Smith, Alice","say ""hi""""#),
+            ["alice", "Smith, Alice", r#"say "hi""#]
+        );
+    }
+
+    #[test]
+    fn csv_without_header() {
+        let rows = parse_rows(
+            "users.csv",
+            "alice,Alice,a@example.org,true,secret\n\nbob\n",
+        )
+        .unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].localpart, "alice");
+        assert_eq!(rows[0].displayname, "Alice");
+        assert_eq!(rows[0].email, "a@example.org");
+        assert_eq!(rows[0].admin, serde_json::json!("true"));
+        assert_eq!(rows[0].password, "secret");
+        assert_eq!(rows[1].localpart, "bob");
+        assert!(rows[1].password.is_empty());
+    }
+
+    #[test]
+    fn csv_with_header() {
+        let rows = parse_rows(
+            "users.csv",
+            "Email, LocalPart,unknown\nb@example.org,bob,x\n",
+        )
+        .unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].localpart, "bob");
+        assert_eq!(rows[0].email, "b@example.org");
+        assert!(rows[0].displayname.is_empty());
+    }
+
+    #[test]
+    fn json_rows() {
+        let rows = parse_rows(
+            "users.txt",
+            r#" [{"localpart": "alice", "admin": true}, {"localpart": "bob", "email": "b@x"}]"#,
+        )
+        .unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].admin, serde_json::json!(true));
+        assert_eq!(rows[1].email, "b@x");
+        assert!(parse_rows("users.json", "{}").is_err());
+        assert!(parse_rows("users.json", "[{\"localpart\": 1}]").is_err());
+    }
+
+    #[test]
+    fn validate_admin() {
+        for (admin, expected) in [
+            (serde_json::json!(null), Some(false)),
+            (serde_json::json!(true), Some(true)),
+            (serde_json::json!(0), Some(false)),
+            (serde_json::json!(2), Some(true)),
+            (serde_json::json!(" Yes "), Some(true)),
+            (serde_json::json!("no"), Some(false)),
+            (serde_json::json!(""), Some(false)),
+            (serde_json::json!("maybe"), None),
+            (serde_json::json!([]), None),
+        ] {
+            let mut r = row("alice", admin);
+            let err = validate_row(&[], &mut r);
+            match expected {
+                Some(is_admin) => {
+                    assert_eq!(err, None);
+                    assert_eq!(r.is_admin, is_admin);
+                }
+                None => assert!(err.is_some()),
+            }
+        }
+    }
+
+    #[test]
+    fn validate_fields() {
+        let mut r = row(" alice ", serde_json::Value::Null);
+        r.email = " a@example.org ".into();
+        assert_eq!(validate_row(&[], &mut r), None);
+        assert_eq!(r.localpart, "alice");
+        assert_eq!(r.email, "a@example.org");
+
+        let invalid = |localpart: &str, email: &str| {
+            let mut r = row(localpart, serde_json::Value::Null);
+            r.email = email.into();
+            validate_row(&[], &mut r)
+        };
+        assert_eq!(invalid("", ""), Some("Missing localpart".into()));
+        assert_eq!(
+            invalid("Alice", ""),
+            Some("Invalid characters in localpart".into())
+        );
+        assert_eq!(
+            invalid("a:b", ""),
+            Some("Invalid characters in localpart".into())
+        );
+        assert_eq!(invalid("alice", "nope"), Some("Invalid email".into()));
+    }
+
+    #[test]
+    fn validate_duplicates() {
+        let previous = [
+            row("alice", serde_json::Value::Null),
+            row("bob", serde_json::Value::Null),
+        ];
+        let mut r = row("bob", serde_json::Value::Null);
+        assert_eq!(
+            validate_row(&previous, &mut r),
+            Some("Duplicate of row 2".into())
+        );
+        let mut r = row("carol", serde_json::Value::Null);
+        assert_eq!(validate_row(&previous, &mut r), None);
+    }
+}
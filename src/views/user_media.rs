@@ -0,0 +1,416 @@
+use crossterm::event::{Event, KeyCode};
+use tui::{
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Style},
+    text::Span,
+    widgets::{Block, Borders, Paragraph},
+};
+
+use crate::{
+    backend::MediaInfoV1,
+    common::{
+        batch::Batch,
+        editable::{Editable, EditableWidget},
+        human::{format_bytes, format_opt_ts, format_ts},
+        prompt::Prompt,
+        table::EditTable,
+        HandleRes, ViewImpl,
+    },
+    state::State,
+};
+
+#[derive(Default)]
+enum CurPrompt {
+    #[default]
+    None,
+    Notice,
+    ConfirmDelete,
+    ConfirmDeleteAll,
+}
+
+const MEDIA_COLUMNS: [&str; 8] = [
+    "Media",
+    "Type",
+    "Size",
+    "Name",
+    "Created",
+    "Last access",
+    "Quarantined by",
+    "Protected",
+];
+const PAGE_SIZE: usize = 100;
+/// Number of media ids listed in confirmation prompts
+const MAX_LISTED_MEDIA: usize = 10;
+
+/// Lists the media uploaded by a user
+pub struct UserMediaView {
+    user_id: String,
+    cur_prompt: CurPrompt,
+    prompt: Prompt,
+    table: EditTable,
+    media: Vec<MediaInfoV1>,
+    /// Number of media of the user on the server
+    total: usize,
+    /// Largest first instead of newest first
+    by_size: bool,
+    /// Rows to delete once confirmed
+    to_delete: Vec<usize>,
+    /// Media ids being deleted
+    deleting: Option<Batch<String>>,
+}
+
+impl UserMediaView {
+    pub fn new(user_id: &str) -> Self {
+        Self {
+            user_id: user_id.to_string(),
+            cur_prompt: CurPrompt::default(),
+            prompt: Prompt::default(),
+            table: EditTable::new(&MEDIA_COLUMNS).with_selection(),
+            media: Vec::new(),
+            total: 0,
+            by_size: false,
+            to_delete: Vec::new(),
+            deleting: None,
+        }
+    }
+
+    fn reload(&mut self, state: &mut State) {
+        self.table.clear();
+        self.media.clear();
+        self.total = 0;
+        self.load_next_chunk(state);
+    }
+
+    fn load_next_chunk(&mut self, state: &mut State) {
+        if !self.media.is_empty() && self.media.len() >= self.total {
+            return;
+        }
+        let l = match state.backend.list_user_media(
+            self.user_id.as_str(),
+            self.media.len(),
+            PAGE_SIZE,
+            self.by_size,
+        ) {
+            Ok(l) => l,
+            Err(e) => {
+                self.prompt.error_notice(e.as_str());
+                self.cur_prompt = CurPrompt::Notice;
+                return;
+            }
+        };
+        self.total = l.total;
+        for m in l.media {
+            self.table.push(vec![
+                Editable::ro_string(m.media_id.as_str()),
+                Editable::ro_string(m.media_type.as_str()),
+                Editable::ro_string(format_bytes(m.media_length).as_str()),
+                Editable::ro_string(m.upload_name.as_deref().unwrap_or("")),
+                Editable::ro_string(format_ts(m.created_ts).as_str()),
+                Editable::ro_string(format_opt_ts(m.last_access_ts).as_str()),
+                Editable::ro_string(m.quarantined_by.as_deref().unwrap_or("-")),
+                Editable::ro_bool(m.safe_from_quarantine),
+            ]);
+            self.media.push(m);
+        }
+    }
+
+    /// Total size of the media in `rows`
+    fn size_of(&self, rows: &[usize]) -> u64 {
+        rows.iter().map(|y| self.media[*y].media_length).sum()
+    }
+
+    fn confirm_delete(&mut self) {
+        self.to_delete = self.table.selection();
+        if self.to_delete.is_empty() {
+            return;
+        }
+        self.prompt.clear();
+        self.prompt.msg = format!(
+            "Delete {} media ({}) of {} ? This cannot be undone",
+            self.to_delete.len(),
+            format_bytes(self.size_of(&self.to_delete)),
+            self.user_id
+        );
+        for y in self.to_delete.iter().take(MAX_LISTED_MEDIA) {
+            let m = &self.media[*y];
+            self.prompt.msg.push_str(&format!(
+                "\n  {} {}",
+                m.media_id,
+                m.upload_name.as_deref().unwrap_or("")
+            ));
+        }
+        if self.to_delete.len() > MAX_LISTED_MEDIA {
+            self.prompt.msg.push_str("\n  ...");
+        }
+        self.prompt.true_button.push_str("Delete");
+        self.prompt.false_button.push_str("Cancel");
+        self.cur_prompt = CurPrompt::ConfirmDelete;
+    }
+
+    /// Quarantines (`m`/`M`) or protects (`p`/`P`) the selected media
+    fn apply_to_selection(&mut self, key: KeyCode, state: &mut State) {
+        let mut errors = String::new();
+        let server_name = state.backend.server_name().to_string();
+        for y in self.table.selection() {
+            let media_id = self.media[y].media_id.as_str();
+            let r = match key {
+                KeyCode::Char('m') => {
+                    state
+                        .backend
+                        .quarantine_media(server_name.as_str(), media_id, true)
+                }
+                KeyCode::Char('M') => {
+                    state
+                        .backend
+                        .quarantine_media(server_name.as_str(), media_id, false)
+                }
+                KeyCode::Char('p') => state.backend.protect_media(media_id, true),
+                KeyCode::Char('P') => state.backend.protect_media(media_id, false),
+                _ => return,
+            };
+            if let Err(e) = r {
+                errors.push_str(&format!("{} : {}\n", media_id, e));
+            }
+        }
+        self.reload(state);
+        if !errors.is_empty() {
+            self.prompt.error_notice(errors.as_str());
+            self.cur_prompt = CurPrompt::Notice;
+        }
+    }
+
+    fn finish_delete(&mut self, state: &mut State) {
+        let batch = match self.deleting.take() {
+            Some(b) => b,
+            None => return,
+        };
+        self.reload(state);
+        self.prompt.clear();
+        self.prompt.msg = format!("Delete media : {}", batch.progress());
+        if !batch.is_done() {
+            self.prompt.msg.push_str(" (stopped)");
+        }
+        for (idx, e) in batch.failures.iter() {
+            self.prompt
+                .error
+                .push_str(&format!("{} : {}\n", batch.items[*idx], e));
+        }
+        self.prompt.true_button.push_str("Ok");
+        self.cur_prompt = CurPrompt::Notice;
+    }
+
+    fn status_line(&self) -> String {
+        if let Some(batch) = self.deleting.as_ref() {
+            return format!("Deleting media : {}", batch.progress());
+        }
+        let all: Vec<usize> = (0..self.media.len()).collect();
+        let selected: Vec<usize> = all
+            .iter()
+            .copied()
+            .filter(|y| self.table.is_selected(*y))
+            .collect();
+        format!(
+            "{}/{} media listed ({}), {} selected ({}) | {}",
+            self.media.len(),
+            self.total,
+            format_bytes(self.size_of(&all)),
+            selected.len(),
+            format_bytes(self.size_of(&selected)),
+            if self.by_size {
+                "largest first"
+            } else {
+                "newest first"
+            }
+        )
+    }
+}
+
+impl ViewImpl<State> for UserMediaView {
+    fn title(&self) -> &'static str {
+        "Media"
+    }
+
+    fn enter_view(&mut self, state: &mut State) {
+        self.reload(state);
+    }
+
+    fn draw_view(
+        &mut self,
+        frame: &mut tui::Frame<tui::backend::CrosstermBackend<&mut std::io::Stdout>>,
+        rect: tui::layout::Rect,
+        _state: &mut State,
+    ) {
+        if !matches!(self.cur_prompt, CurPrompt::None) {
+            self.prompt.draw_view(frame, rect, &mut ());
+            return;
+        }
+        let rects = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(0)])
+            .split(rect);
+        frame.render_widget(
+            Paragraph::new(Span::styled(
+                self.status_line(),
+                Style::default().fg(Color::Yellow),
+            )),
+            rects[0],
+        );
+        let title = format!("Media of {}", self.user_id);
+        self.table.draw(
+            frame,
+            rects[1],
+            Block::default().borders(Borders::ALL).title(title),
+        );
+    }
+
+    fn handle_event(&mut self, event: &Event, state: &mut State) -> HandleRes {
+        match self.cur_prompt {
+            CurPrompt::Notice => {
+                let r = self.prompt.handle_event(event, &mut ());
+                if let HandleRes::Exit(_) = r {
+                    self.cur_prompt = CurPrompt::None;
+                    return HandleRes::ReDraw;
+                }
+                return r;
+            }
+            CurPrompt::ConfirmDelete => {
+                let r = self.prompt.handle_event(event, &mut ());
+                if let HandleRes::Exit(confirmed) = r {
+                    self.cur_prompt = CurPrompt::None;
+                    if confirmed {
+                        let ids = self
+                            .to_delete
+                            .drain(..)
+                            .map(|y| self.media[y].media_id.clone())
+                            .collect();
+                        self.deleting = Some(Batch::new(ids));
+                    }
+                    return HandleRes::ReDraw;
+                }
+                return r;
+            }
+            CurPrompt::ConfirmDeleteAll => {
+                let r = self.prompt.handle_event(event, &mut ());
+                if let HandleRes::Exit(confirmed) = r {
+                    if confirmed {
+                        if self.prompt.fields[0].1.as_str().trim() != self.user_id {
+                            self.prompt.error = format!("Type '{}' to confirm", self.user_id);
+                            return HandleRes::ReDraw;
+                        }
+                        match state.backend.delete_user_media(self.user_id.as_str()) {
+                            Ok(n) => self.prompt.notice(&format!("Deleted {} media", n)),
+                            Err(e) => self.prompt.error_notice(e.as_str()),
+                        }
+                        self.cur_prompt = CurPrompt::Notice;
+                        self.reload(state);
+                    } else {
+                        self.cur_prompt = CurPrompt::None;
+                    }
+                    return HandleRes::ReDraw;
+                }
+                return r;
+            }
+            CurPrompt::None => {}
+        }
+
+        let key = match event {
+            Event::Key(k) => k,
+            _ => return HandleRes::Ignored,
+        };
+
+        if self.deleting.is_some() {
+            // Only allow stopping the deletion
+            if let KeyCode::Esc = key.code {
+                self.finish_delete(state);
+                return HandleRes::ReDraw;
+            }
+            return HandleRes::Handled;
+        }
+
+        let r = self.table.handle_event(key);
+        if !matches!(r, HandleRes::Ignored) {
+            // Fetch more media when reaching the end of the list
+            if self.table.focus_y + 1 >= self.table.len() {
+                self.load_next_chunk(state);
+            }
+            return r;
+        }
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => HandleRes::Exit(false),
+            KeyCode::F(5) => {
+                self.reload(state);
+                HandleRes::ReDraw
+            }
+            KeyCode::Char('s') => {
+                self.by_size = !self.by_size;
+                self.reload(state);
+                HandleRes::ReDraw
+            }
+            KeyCode::Char('a') => {
+                while self.media.len() < self.total {
+                    let len = self.media.len();
+                    self.load_next_chunk(state);
+                    if self.media.len() == len {
+                        break;
+                    }
+                }
+                let all_selected = self.table.num_selected() == self.table.len();
+                self.table.select_all(!all_selected);
+                HandleRes::ReDraw
+            }
+            KeyCode::Delete | KeyCode::Char('d') => {
+                self.confirm_delete();
+                HandleRes::ReDraw
+            }
+            KeyCode::Char('m' | 'M' | 'p' | 'P') => {
+                self.apply_to_selection(key.code, state);
+                HandleRes::ReDraw
+            }
+            KeyCode::Char('D') => {
+                self.prompt.clear();
+                self.prompt.msg = format!(
+                    "Delete all the {} media of {} ? This cannot be undone\nType the user id to confirm",
+                    self.total, self.user_id
+                );
+                self.prompt
+                    .fields
+                    .push(("Confirm".into(), Editable::string("")));
+                self.prompt.true_button.push_str("Delete all");
+                self.prompt.false_button.push_str("Cancel");
+                self.cur_prompt = CurPrompt::ConfirmDeleteAll;
+                HandleRes::ReDraw
+            }
+            _ => HandleRes::Ignored,
+        }
+    }
+
+    fn tick(&mut self, state: &mut State) -> HandleRes {
+        let batch = match self.deleting.as_mut() {
+            Some(b) => b,
+            None => return HandleRes::Ignored,
+        };
+        batch.step(|media_id| state.backend.delete_media(media_id.as_str()));
+        if batch.is_done() {
+            self.finish_delete(state);
+        }
+        HandleRes::ReDraw
+    }
+
+    fn hints(&self) -> Vec<(&'static str, &'static str)> {
+        if self.deleting.is_some() {
+            return vec![("Esc", "Stop")];
+        }
+        vec![
+            ("Space", "Select"),
+            ("a", "Select all"),
+            ("s", "Sort by size/date"),
+            ("d/Del", "Delete"),
+            ("D", "Delete all"),
+            ("m/M", "Quarantine/Lift"),
+            ("p/P", "Protect/Unprotect"),
+            ("F5", "Reload"),
+            ("Esc", "Back"),
+        ]
+    }
+}
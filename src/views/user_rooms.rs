@@ -0,0 +1,402 @@
+use std::time::{Duration, Instant};
+
+use crossterm::event::{Event, KeyCode};
+use tui::{
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Style},
+    text::Span,
+    widgets::{Block, Borders, Paragraph},
+};
+
+use crate::{
+    common::{
+        editable::{Editable, EditableWidget},
+        prompt::Prompt,
+        table::EditTable,
+        Goto, HandleRes, ViewImpl,
+    },
+    state::State,
+};
+
+#[derive(Default)]
+enum CurPrompt {
+    #[default]
+    None,
+    Notice,
+    ConfirmRemove,
+    ConfirmRedact,
+}
+
+const ROOM_COLUMNS: [&str; 4] = ["Room", "Name", "Alias", "Members"];
+/// Number of rooms listed in confirmation prompts
+const MAX_LISTED_ROOMS: usize = 10;
+/// Number of events listed in the report of a redaction
+const MAX_LISTED_EVENTS: usize = 10;
+/// How often the progress of a running redaction is fetched
+const REDACT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Lists the rooms a user is joined to
+pub struct UserRoomsView {
+    user_id: String,
+    cur_prompt: CurPrompt,
+    prompt: Prompt,
+    table: EditTable,
+    /// Number of rows whose room details were fetched
+    resolved: usize,
+    /// Rows to remove the user from once confirmed
+    to_remove: Vec<usize>,
+    /// Id of the running redaction and when its status was last fetched
+    redact: Option<(String, Instant)>,
+    /// Progress of the last redaction
+    redact_status: String,
+    /// Report of the redaction and whether it is an error, shown once no prompt is open
+    pending_notice: Option<(String, bool)>,
+}
+
+impl UserRoomsView {
+    pub fn new(user_id: &str) -> Self {
+        Self {
+            user_id: user_id.to_string(),
+            cur_prompt: CurPrompt::default(),
+            prompt: Prompt::default(),
+            table: EditTable::new(&ROOM_COLUMNS).with_selection(),
+            resolved: 0,
+            to_remove: Vec::new(),
+            redact: None,
+            redact_status: String::new(),
+            pending_notice: None,
+        }
+    }
+
+    /// Lists the room ids, their details are fetched in the background
+    fn reload(&mut self, state: &mut State) {
+        self.table.clear();
+        self.resolved = 0;
+        let rooms = match state.backend.user_joined_rooms(self.user_id.as_str()) {
+            Ok(r) => r,
+            Err(e) => {
+                self.prompt.error_notice(e.as_str());
+                self.cur_prompt = CurPrompt::Notice;
+                return;
+            }
+        };
+        for room_id in rooms.iter() {
+            self.table.push(vec![
+                Editable::ro_string(room_id.as_str()),
+                Editable::ro_string("..."),
+                Editable::ro_string(""),
+                Editable::ro_string(""),
+            ]);
+        }
+    }
+
+    fn room_id(&self, y: usize) -> &str {
+        self.table.rows[y][0].as_str()
+    }
+
+    fn remove_user(&mut self, reason: &str, state: &mut State) {
+        let mut errors = String::new();
+        let targets: Vec<usize> = self.to_remove.drain(..).collect();
+        for y in targets.iter() {
+            let room_id = self.room_id(*y).to_string();
+            if let Err(e) = state
+                .backend
+                .kick_user(room_id.as_str(), self.user_id.as_str(), reason)
+            {
+                errors.push_str(&format!("{} : {}\n", room_id, e));
+            }
+        }
+        if !errors.is_empty() {
+            self.prompt.error_notice(errors.as_str());
+            self.cur_prompt = CurPrompt::Notice;
+        }
+        self.reload(state);
+    }
+
+    fn confirm_redact(&mut self) {
+        let selected = self.table.num_selected();
+        self.prompt.clear();
+        self.prompt.msg = format!(
+            "Redact every event {} sent ?\nWithout a selection, all the rooms the user is joined to are cleaned up",
+            self.user_id
+        );
+        self.prompt
+            .fields
+            .push(("Reason".into(), Editable::string("")));
+        if selected > 0 {
+            self.prompt.fields.push((
+                format!("Only the {} selected rooms", selected),
+                Editable::bool(true),
+            ));
+        }
+        self.prompt.true_button.push_str("Redact");
+        self.prompt.false_button.push_str("Cancel");
+        self.cur_prompt = CurPrompt::ConfirmRedact;
+    }
+
+    fn start_redact(&mut self, state: &mut State) -> Result<(), String> {
+        let reason = self.prompt.fields[0].1.as_str().trim().to_string();
+        let only_selected =
+            matches!(self.prompt.fields.get(1), Some((_, f)) if f.as_str() == "true");
+        // No room means every joined room
+        let rooms: Vec<String> = if only_selected {
+            self.table
+                .selection()
+                .iter()
+                .map(|y| self.room_id(*y).to_string())
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let redact_id =
+            state
+                .backend
+                .redact_user_events(self.user_id.as_str(), &rooms, reason.as_str())?;
+        self.redact_status = format!("Redacting ({}) : scheduled", redact_id);
+        self.redact = Some((redact_id, Instant::now()));
+        Ok(())
+    }
+
+    fn poll_redact(&mut self, state: &mut State) -> HandleRes {
+        let (redact_id, last_poll) = match self.redact.as_mut() {
+            Some(r) => r,
+            None => return HandleRes::Ignored,
+        };
+        if last_poll.elapsed() < REDACT_POLL_INTERVAL {
+            return HandleRes::Ignored;
+        }
+        *last_poll = Instant::now();
+        let status = match state.backend.redact_user_events_status(redact_id.as_str()) {
+            Ok(s) => s,
+            Err(e) => {
+                self.redact_status = format!("Redacting ({}) : {}", redact_id, e);
+                return HandleRes::ReDraw;
+            }
+        };
+        // Synapse only reports the events it failed to redact, not the redacted ones
+        let failed = status.failed_redactions.len();
+        self.redact_status = format!(
+            "Redacting ({}) : {}, {} failed",
+            redact_id, status.status, failed
+        );
+        if !matches!(status.status.as_str(), "completed" | "failed") {
+            return HandleRes::ReDraw;
+        }
+        self.redact = None;
+        let mut msg = format!(
+            "Redacting the events of {} {}\n{} events could not be redacted",
+            self.user_id, status.status, failed
+        );
+        for (event_id, error) in status.failed_redactions.iter().take(MAX_LISTED_EVENTS) {
+            msg.push_str(&format!("\n  {} : {}", event_id, error));
+        }
+        if failed > MAX_LISTED_EVENTS {
+            msg.push_str("\n  ...");
+        }
+        self.pending_notice = Some((msg, status.status == "failed" || failed > 0));
+        HandleRes::ReDraw
+    }
+
+    /// Shows the report of the redaction, unless the user is busy with a prompt
+    fn show_pending_notice(&mut self) -> HandleRes {
+        if !matches!(self.cur_prompt, CurPrompt::None) {
+            return HandleRes::Ignored;
+        }
+        match self.pending_notice.take() {
+            Some((msg, true)) => self.prompt.error_notice(msg.as_str()),
+            Some((msg, false)) => self.prompt.notice(msg.as_str()),
+            None => return HandleRes::Ignored,
+        }
+        self.cur_prompt = CurPrompt::Notice;
+        HandleRes::ReDraw
+    }
+}
+
+impl ViewImpl<State> for UserRoomsView {
+    fn title(&self) -> &'static str {
+        "Rooms"
+    }
+
+    fn enter_view(&mut self, state: &mut State) {
+        self.reload(state);
+    }
+
+    fn draw_view(
+        &mut self,
+        frame: &mut tui::Frame<tui::backend::CrosstermBackend<&mut std::io::Stdout>>,
+        rect: tui::layout::Rect,
+        _state: &mut State,
+    ) {
+        if !matches!(self.cur_prompt, CurPrompt::None) {
+            self.prompt.draw_view(frame, rect, &mut ());
+            return;
+        }
+        let rects = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(0)])
+            .split(rect);
+        let mut status = format!("{} selected", self.table.num_selected());
+        if self.resolved < self.table.len() {
+            status.push_str(&format!(
+                " | resolving rooms {}/{}",
+                self.resolved,
+                self.table.len()
+            ));
+        }
+        if !self.redact_status.is_empty() {
+            status.push_str(" | ");
+            status.push_str(self.redact_status.as_str());
+        }
+        frame.render_widget(
+            Paragraph::new(Span::styled(status, Style::default().fg(Color::Yellow))),
+            rects[0],
+        );
+        let title = format!("Rooms of {} ({})", self.user_id, self.table.len());
+        self.table.draw(
+            frame,
+            rects[1],
+            Block::default().borders(Borders::ALL).title(title),
+        );
+    }
+
+    fn handle_event(&mut self, event: &Event, state: &mut State) -> HandleRes {
+        match self.cur_prompt {
+            CurPrompt::Notice => {
+                let r = self.prompt.handle_event(event, &mut ());
+                if let HandleRes::Exit(_) = r {
+                    self.cur_prompt = CurPrompt::None;
+                    return HandleRes::ReDraw;
+                }
+                return r;
+            }
+            CurPrompt::ConfirmRemove => {
+                let r = self.prompt.handle_event(event, &mut ());
+                if let HandleRes::Exit(confirmed) = r {
+                    self.cur_prompt = CurPrompt::None;
+                    if confirmed {
+                        let reason = self.prompt.fields[0].1.as_str().trim().to_string();
+                        self.remove_user(reason.as_str(), state);
+                    }
+                    return HandleRes::ReDraw;
+                }
+                return r;
+            }
+            CurPrompt::ConfirmRedact => {
+                let r = self.prompt.handle_event(event, &mut ());
+                if let HandleRes::Exit(confirmed) = r {
+                    self.cur_prompt = CurPrompt::None;
+                    if confirmed {
+                        if let Err(e) = self.start_redact(state) {
+                            self.prompt.error_notice(e.as_str());
+                            self.cur_prompt = CurPrompt::Notice;
+                        }
+                    }
+                    return HandleRes::ReDraw;
+                }
+                return r;
+            }
+            CurPrompt::None => {}
+        }
+
+        let key = match event {
+            Event::Key(k) => k,
+            _ => return HandleRes::Ignored,
+        };
+
+        if let KeyCode::Enter = key.code {
+            if let Some(y) = self.table.cur_row() {
+                state.goto(Goto::Room(self.room_id(y).to_string()));
+            }
+            return HandleRes::ReDraw;
+        }
+
+        let r = self.table.handle_event(key);
+        if !matches!(r, HandleRes::Ignored) {
+            return r;
+        }
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => HandleRes::Exit(false),
+            KeyCode::F(5) => {
+                self.reload(state);
+                HandleRes::ReDraw
+            }
+            KeyCode::Delete | KeyCode::Char('k') => {
+                self.to_remove = self.table.selection();
+                if self.to_remove.is_empty() {
+                    return HandleRes::Handled;
+                }
+                self.prompt.clear();
+                self.prompt.msg = format!(
+                    "Remove {} from {} room(s) ?",
+                    self.user_id,
+                    self.to_remove.len()
+                );
+                for y in self.to_remove.iter().take(MAX_LISTED_ROOMS) {
+                    let row = &self.table.rows[*y];
+                    self.prompt.msg.push_str("\n  ");
+                    self.prompt.msg.push_str(row[0].as_str());
+                    if !row[1].as_str().is_empty() {
+                        self.prompt.msg.push_str(&format!(" ({})", row[1].as_str()));
+                    }
+                }
+                if self.to_remove.len() > MAX_LISTED_ROOMS {
+                    self.prompt.msg.push_str("\n  ...");
+                }
+                self.prompt.msg.push_str(
+                    "\nThe user is kicked, your account must be in each room with enough power",
+                );
+                self.prompt
+                    .fields
+                    .push(("Reason".into(), Editable::string("")));
+                self.prompt.true_button.push_str("Remove");
+                self.prompt.false_button.push_str("Cancel");
+                self.cur_prompt = CurPrompt::ConfirmRemove;
+                HandleRes::ReDraw
+            }
+            KeyCode::Char('x') => {
+                if self.redact.is_some() {
+                    return HandleRes::Handled;
+                }
+                self.confirm_redact();
+                HandleRes::ReDraw
+            }
+            _ => HandleRes::Ignored,
+        }
+    }
+
+    fn tick(&mut self, state: &mut State) -> HandleRes {
+        let r = match (self.poll_redact(state), self.show_pending_notice()) {
+            (HandleRes::Ignored, r) => r,
+            (r, _) => r,
+        };
+        // Resolve one room at a time so the list stays responsive
+        let y = self.resolved;
+        if y >= self.table.len() {
+            return r;
+        }
+        let room_id = self.room_id(y).to_string();
+        let row = &mut self.table.rows[y];
+        match state.backend.room_details(room_id.as_str()) {
+            Ok(d) => {
+                row[1] = Editable::ro_string(d.name.as_deref().unwrap_or(""));
+                row[2] = Editable::ro_string(d.canonical_alias.as_deref().unwrap_or(""));
+                row[3] = Editable::ro_string(d.joined_members.to_string().as_str());
+            }
+            Err(e) => row[1] = Editable::ro_string(e.as_str()),
+        }
+        self.resolved += 1;
+        HandleRes::ReDraw
+    }
+
+    fn hints(&self) -> Vec<(&'static str, &'static str)> {
+        vec![
+            ("Enter", "Open room"),
+            ("Space", "Select"),
+            ("k/Del", "Remove from room"),
+            ("x", "Redact user events"),
+            ("F5", "Reload"),
+            ("Esc", "Back"),
+        ]
+    }
+}
@@ -0,0 +1,260 @@
+use crossterm::event::{Event, KeyCode};
+use tui::{
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Style},
+    text::Span,
+    widgets::{Block, Borders, Paragraph},
+};
+
+use crate::{
+    backend::WhoisV1,
+    common::{
+        copy_to_clipboard,
+        human::format_ts,
+        prompt::Prompt,
+        tree::{Tree, TreeNode},
+        HandleRes, ViewImpl,
+    },
+    state::State,
+};
+
+/// Tree of the devices -> sessions -> connections of a user
+pub struct UserWhoisView {
+    user_id: String,
+    show_prompt: bool,
+    prompt: Prompt,
+    whois: WhoisV1,
+    tree: Tree,
+    /// Most recent first instead of the server's order
+    sort_by_last_seen: bool,
+    status: String,
+}
+
+impl UserWhoisView {
+    pub fn new(user_id: &str) -> Self {
+        Self {
+            user_id: user_id.to_string(),
+            show_prompt: false,
+            prompt: Prompt::default(),
+            whois: WhoisV1::default(),
+            tree: Tree::default(),
+            sort_by_last_seen: true,
+            status: String::new(),
+        }
+    }
+
+    fn reload(&mut self, state: &mut State) {
+        match state.backend.whois(self.user_id.as_str()) {
+            Ok(w) => self.whois = w,
+            Err(e) => {
+                self.prompt.error_notice(e.as_str());
+                self.show_prompt = true;
+            }
+        }
+        self.build_tree();
+    }
+
+    /// Rebuilds the tree from the whois data, keeping the marked IPs
+    fn build_tree(&mut self) {
+        let marked: Vec<String> = self.tree.marked().iter().map(|n| n.data.clone()).collect();
+
+        let mut devices: Vec<_> = self.whois.devices.iter().collect();
+        let last_seen = |sessions: &[crate::backend::WhoisSessionV1]| {
+            sessions
+                .iter()
+                .flat_map(|s| s.connections.iter())
+                .map(|c| c.last_seen)
+                .max()
+                .unwrap_or(0)
+        };
+        if self.sort_by_last_seen {
+            devices.sort_by_key(|(_, d)| std::cmp::Reverse(last_seen(&d.sessions)));
+        } else {
+            devices.sort_by(|a, b| a.0.cmp(b.0));
+        }
+
+        let mut roots = Vec::with_capacity(devices.len());
+        for (device_id, device) in devices {
+            let mut sessions: Vec<_> = device.sessions.iter().collect();
+            if self.sort_by_last_seen {
+                sessions.sort_by_key(|s| std::cmp::Reverse(last_seen(std::slice::from_ref(*s))));
+            }
+            let mut session_nodes = Vec::with_capacity(sessions.len());
+            for (idx, session) in sessions.iter().enumerate() {
+                let mut connections: Vec<_> = session.connections.iter().collect();
+                if self.sort_by_last_seen {
+                    connections.sort_by_key(|c| std::cmp::Reverse(c.last_seen));
+                }
+                let connection_nodes = connections
+                    .iter()
+                    .map(|c| {
+                        let mut n = TreeNode::new(format!(
+                            "{:<39} {} {}",
+                            c.ip,
+                            format_ts(c.last_seen),
+                            c.user_agent
+                        ))
+                        .with_data(c.ip.as_str());
+                        n.marked = marked.contains(&c.ip);
+                        n
+                    })
+                    .collect();
+                session_nodes.push(
+                    TreeNode::new(format!(
+                        "Session {} : {} connection(s)",
+                        idx + 1,
+                        session.connections.len()
+                    ))
+                    .with_children(connection_nodes)
+                    .expanded(),
+                );
+            }
+            roots.push(
+                TreeNode::new(format!(
+                    "Device {} : {} session(s), last seen {}",
+                    if device_id.is_empty() {
+                        "(unknown)"
+                    } else {
+                        device_id.as_str()
+                    },
+                    device.sessions.len(),
+                    format_ts(last_seen(&device.sessions))
+                ))
+                .with_children(session_nodes)
+                .expanded(),
+            );
+        }
+        self.tree.roots = roots;
+        self.tree.markable = true;
+    }
+
+    /// Copies the marked IPs, or the IPs under the focused node
+    fn copy_ips(&mut self) {
+        let mut ips: Vec<String> = Vec::new();
+        let mut add = |n: &TreeNode| {
+            if !n.data.is_empty() && !ips.contains(&n.data) {
+                ips.push(n.data.clone());
+            }
+        };
+        let marked = self.tree.marked();
+        if marked.is_empty() {
+            if let Some(n) = self.tree.focused() {
+                n.walk(&mut |n| add(n));
+            }
+        } else {
+            for n in marked {
+                add(n);
+            }
+        }
+        if ips.is_empty() {
+            return;
+        }
+        self.status = match copy_to_clipboard(ips.join("\n").as_str()) {
+            Ok(_) => format!("Copied {} IP(s) to the clipboard", ips.len()),
+            Err(e) => format!("Failed to copy : {}", e),
+        };
+    }
+}
+
+impl ViewImpl<State> for UserWhoisView {
+    fn title(&self) -> &'static str {
+        "Whois"
+    }
+
+    fn enter_view(&mut self, state: &mut State) {
+        self.reload(state);
+    }
+
+    fn draw_view(
+        &mut self,
+        frame: &mut tui::Frame<tui::backend::CrosstermBackend<&mut std::io::Stdout>>,
+        rect: tui::layout::Rect,
+        _state: &mut State,
+    ) {
+        if self.show_prompt {
+            self.prompt.draw_view(frame, rect, &mut ());
+            return;
+        }
+        let rects = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(0)])
+            .split(rect);
+        frame.render_widget(
+            Paragraph::new(Span::styled(
+                self.status.as_str(),
+                Style::default().fg(Color::Yellow),
+            )),
+            rects[0],
+        );
+        let title = format!(
+            "Sessions of {}{}",
+            self.user_id,
+            if self.sort_by_last_seen {
+                " (most recent first)"
+            } else {
+                ""
+            }
+        );
+        self.tree.draw(
+            frame,
+            rects[1],
+            Block::default().borders(Borders::ALL).title(title),
+        );
+    }
+
+    fn handle_event(&mut self, event: &Event, state: &mut State) -> HandleRes {
+        if self.show_prompt {
+            let r = self.prompt.handle_event(event, &mut ());
+            if let HandleRes::Exit(_) = r {
+                self.show_prompt = false;
+                return HandleRes::ReDraw;
+            }
+            return r;
+        }
+
+        let key = match event {
+            Event::Key(k) => k,
+            _ => return HandleRes::Ignored,
+        };
+
+        let r = self.tree.handle_event(key);
+        if !matches!(r, HandleRes::Ignored) {
+            return r;
+        }
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => HandleRes::Exit(false),
+            KeyCode::F(5) => {
+                self.reload(state);
+                HandleRes::ReDraw
+            }
+            KeyCode::Char('s') => {
+                self.sort_by_last_seen = !self.sort_by_last_seen;
+                self.build_tree();
+                HandleRes::ReDraw
+            }
+            KeyCode::Char('e') => {
+                let all_expanded = self.tree.roots.iter().all(|n| n.expanded);
+                self.tree.expand_all(!all_expanded);
+                HandleRes::ReDraw
+            }
+            KeyCode::Char('c') => {
+                self.copy_ips();
+                HandleRes::ReDraw
+            }
+            _ => HandleRes::Ignored,
+        }
+    }
+
+    fn hints(&self) -> Vec<(&'static str, &'static str)> {
+        vec![
+            ("Enter/←/→", "Collapse/Expand"),
+            ("e", "Expand all"),
+            ("Space", "Mark"),
+            ("c", "Copy IPs"),
+            ("s", "Sort by last seen"),
+            ("F5", "Reload"),
+            ("Esc", "Back"),
+        ]
+    }
+}
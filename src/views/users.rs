@@ -1,55 +1,156 @@
 use crossterm::event::{Event, KeyCode};
 use tui::{
-    layout::Constraint,
-    style::{Color, Modifier, Style},
-    text::{Span, Spans},
-    widgets::{Block, Borders, Cell, Row, Table, TableState},
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Style},
+    text::Span,
+    widgets::{Block, Borders, Paragraph},
 };
 
 use crate::{
-    backend::Synapse,
+    backend::{Synapse, UserFilter, UserModifyV2},
     common::{
-        apply_offset,
+        batch::Batch,
         editable::{Editable, EditableWidget},
         prompt::Prompt,
+        table::EditTable,
         HandleRes, ViewImpl,
     },
     state::State,
+    views::{
+        NoticeTargets, ServerNoticeView, UserDevicesView, UserImportView, UserMediaView,
+        UserRoomsView, UserWhoisView,
+    },
 };
 
+#[derive(Default)]
 enum CurPrompt {
+    #[default]
     None,
     Notice,
-    SaveChanges,
-}
-impl Default for CurPrompt {
-    fn default() -> Self {
-        Self::None
-    }
+    Filter,
+    Confirm(BulkAction),
 }
 
+#[derive(Default)]
 enum SyncState {
+    #[default]
     Some,
     Max,
 }
-impl Default for SyncState {
-    fn default() -> Self {
-        Self::Some
+
+/// Actions that can be applied to all the selected users
+#[derive(Clone)]
+enum BulkAction {
+    /// Deactivate and optionally erase the users' data
+    Deactivate(bool),
+    /// Reactivate, setting a new password if required by the server
+    Reactivate(Option<String>),
+    MakeAdmin,
+    RevokeAdmin,
+    ShadowBan,
+    LiftShadowBan,
+    Logout,
+    ResetRateLimit,
+}
+impl BulkAction {
+    fn from_key(key: KeyCode) -> Option<Self> {
+        Some(match key {
+            KeyCode::Char('d') => Self::Deactivate(false),
+            KeyCode::Char('r') => Self::Reactivate(None),
+            KeyCode::Char('m') => Self::MakeAdmin,
+            KeyCode::Char('M') => Self::RevokeAdmin,
+            KeyCode::Char('s') => Self::ShadowBan,
+            KeyCode::Char('S') => Self::LiftShadowBan,
+            KeyCode::Char('l') => Self::Logout,
+            KeyCode::Char('R') => Self::ResetRateLimit,
+            _ => return None,
+        })
+    }
+
+    fn desc(&self) -> &'static str {
+        match self {
+            Self::Deactivate(false) => "Deactivate",
+            Self::Deactivate(true) => "Deactivate and erase",
+            Self::Reactivate(_) => "Reactivate",
+            Self::MakeAdmin => "Make admin",
+            Self::RevokeAdmin => "Revoke admin from",
+            Self::ShadowBan => "Shadow-ban",
+            Self::LiftShadowBan => "Lift the shadow-ban of",
+            Self::Logout => "Log out all devices of",
+            Self::ResetRateLimit => "Reset rate limits of",
+        }
+    }
+
+    fn apply(&self, synapse: &mut Synapse, user_id: &str) -> Result<(), String> {
+        match self {
+            Self::Deactivate(erase) => synapse.deactivate_user(user_id, *erase),
+            Self::Reactivate(password) => synapse.modify_user(
+                user_id,
+                &UserModifyV2 {
+                    deactivated: Some(false),
+                    password: password.clone(),
+                    ..Default::default()
+                },
+            ),
+            Self::MakeAdmin => synapse.set_admin(user_id, true),
+            Self::RevokeAdmin => synapse.set_admin(user_id, false),
+            Self::ShadowBan => synapse.set_shadow_banned(user_id, true),
+            Self::LiftShadowBan => synapse.set_shadow_banned(user_id, false),
+            Self::Logout => synapse.logout_user(user_id).map(|_| ()),
+            Self::ResetRateLimit => synapse.reset_rate_limit(user_id),
+        }
+    }
+
+    /// Reflects a successful action in the user's row
+    fn update_row(&self, row: &mut [Editable]) {
+        match self {
+            Self::Deactivate(_) => row[ACTIVE_COLUMN] = Editable::bool(false),
+            Self::Reactivate(_) => row[ACTIVE_COLUMN] = Editable::bool(true),
+            Self::MakeAdmin => row[ADMIN_COLUMN] = Editable::bool(true),
+            Self::RevokeAdmin => row[ADMIN_COLUMN] = Editable::bool(false),
+            Self::ShadowBan => row[SHADOW_BANNED_COLUMN] = Editable::bool(true),
+            Self::LiftShadowBan => row[SHADOW_BANNED_COLUMN] = Editable::bool(false),
+            _ => {}
+        }
     }
 }
 
-const USER_COLUMNS: [&str; 5] = ["ID", "Name", "Admin", "Guest", "Active"];
+const USER_COLUMNS: [&str; 6] = ["ID", "Name", "Admin", "Guest", "Active", "Shadow-banned"];
+const NAME_COLUMN: usize = 1;
+const ADMIN_COLUMN: usize = 2;
+const ACTIVE_COLUMN: usize = 4;
+const SHADOW_BANNED_COLUMN: usize = 5;
+const PAGE_SIZE: usize = 32;
+/// Number of user ids listed in confirmation prompts
+const MAX_LISTED_USERS: usize = 10;
 
-#[derive(Default)]
 pub struct UsersView {
     cur_prompt: CurPrompt,
     prompt: Prompt,
     sync_state: SyncState,
-    editing: bool,
-    focus_x: usize,
-    focus_y: usize,
-    user_list: Vec<[Editable; USER_COLUMNS.len()]>,
-    user_list_state: TableState,
+    filter: UserFilter,
+    user_table: EditTable,
+    /// Rows targeted by the action being confirmed
+    confirm_targets: Vec<usize>,
+    /// Action currently being applied to the selected users (row, user id)
+    bulk: Option<(BulkAction, Batch<(usize, String)>)>,
+    /// View opened on top of the user list
+    sub_view: Option<Box<dyn ViewImpl<State>>>,
+}
+
+impl Default for UsersView {
+    fn default() -> Self {
+        Self {
+            cur_prompt: CurPrompt::default(),
+            prompt: Prompt::default(),
+            sync_state: SyncState::default(),
+            filter: UserFilter::default(),
+            user_table: EditTable::new(&USER_COLUMNS).with_selection(),
+            confirm_targets: Vec::new(),
+            bulk: None,
+            sub_view: None,
+        }
+    }
 }
 
 impl ViewImpl<State> for UsersView {
@@ -58,9 +159,7 @@ impl ViewImpl<State> for UsersView {
     }
     fn enter_view(&mut self, state: &mut State) {
         if let Err(e) = self.load_next_chunk(&mut state.backend) {
-            self.prompt.clear();
-            self.prompt.error.push_str(e.as_str());
-            self.prompt.true_button.push_str("Ok");
+            self.prompt.error_notice(e.as_str());
             self.cur_prompt = CurPrompt::Notice;
         }
     }
@@ -68,209 +167,508 @@ impl ViewImpl<State> for UsersView {
         &mut self,
         frame: &mut tui::Frame<tui::backend::CrosstermBackend<&mut std::io::Stdout>>,
         rect: tui::layout::Rect,
-        _state: &mut State,
+        state: &mut State,
     ) {
+        if let Some(v) = self.sub_view.as_mut() {
+            v.draw_view(frame, rect, state);
+            return;
+        }
+
         if !matches!(self.cur_prompt, CurPrompt::None) {
             self.prompt.draw_view(frame, rect, &mut ());
             return;
         }
 
-        let header_cells = USER_COLUMNS.iter().map(|v| Cell::from(*v));
-        let header_row = Row::new(header_cells)
-            .height(1)
-            .style(Style::default().fg(Color::DarkGray));
+        let rects = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(0)])
+            .split(rect);
+        frame.render_widget(
+            Paragraph::new(Span::styled(
+                self.status_line(),
+                Style::default().fg(Color::Yellow),
+            )),
+            rects[0],
+        );
+        self.user_table.draw(
+            frame,
+            rects[1],
+            Block::default().borders(Borders::LEFT | Borders::RIGHT),
+        );
+    }
 
-        let mut table_spans = Vec::with_capacity(self.user_list.len());
-        let mut widths = [Constraint::Length(7); USER_COLUMNS.len()];
-        for y in 0..self.user_list.len() {
-            let mut items = Vec::with_capacity(USER_COLUMNS.len());
-            for x in 0..USER_COLUMNS.len() {
-                let i = &self.user_list[y][x];
-                let cur_focused = y == self.focus_y && x == self.focus_x;
-                let editing_cur = self.editing && cur_focused;
-                let val_changed = i.is_changed();
-                let mut spans = i.as_spans(editing_cur);
-                // Color any changed value
-                if val_changed {
-                    for s in spans.iter_mut() {
-                        if val_changed {
-                            s.style = s.style.fg(Color::Yellow);
-                        }
-                    }
-                }
-                let mut width_padding = 2;
-                // Style the current focused item
-                if cur_focused {
-                    width_padding = 0;
-                    self.user_list_state.select(Some(self.focus_y));
-                    let s = if i.is_editable() {
-                        if self.editing {
-                            Style::default()
-                                .fg(Color::Yellow)
-                                .add_modifier(Modifier::BOLD)
-                        } else {
-                            Style::default()
-                                .fg(Color::Green)
-                                .add_modifier(Modifier::BOLD)
-                        }
-                    } else {
-                        Style::default().fg(Color::Black)
-                    };
-                    spans.insert(0, Span::styled("[", s));
-                    spans.push(Span::styled("]", s));
+    fn handle_event(&mut self, event: &Event, state: &mut State) -> HandleRes {
+        if let Some(v) = self.sub_view.as_mut() {
+            let r = v.handle_event(event, state);
+            if let HandleRes::Exit(changed) = r {
+                self.sub_view = None;
+                // The sub view modified users, refresh the list
+                if changed {
+                    self.reload(&mut state.backend);
                 }
-                let s = Spans::from(spans);
-                match &mut widths[x] {
-                    Constraint::Length(v) => {
-                        if s.width() as u16 + width_padding > *v {
-                            *v = s.width() as u16 + width_padding;
-                        }
-                    }
-                    _ => unreachable!(),
-                };
-                items.push(Spans::from(s));
+                return HandleRes::ReDraw;
             }
-            table_spans.push(Row::new(items));
+            return r;
         }
 
-        let user_table = Table::new(table_spans)
-            .block(Block::default().borders(Borders::LEFT | Borders::RIGHT))
-            .header(header_row)
-            .highlight_style(Style::default().bg(Color::DarkGray))
-            .widths(&widths);
-        frame.render_stateful_widget(user_table, rect, &mut self.user_list_state);
-    }
-
-    fn handle_event(&mut self, event: &Event, state: &mut State) -> HandleRes {
         match self.cur_prompt {
             CurPrompt::Notice => {
-                if let HandleRes::Exit(_) = self.prompt.handle_event(event, &mut ()) {
+                let r = self.prompt.handle_event(event, &mut ());
+                if let HandleRes::Exit(_) = r {
                     self.cur_prompt = CurPrompt::None;
                     return HandleRes::ReDraw;
                 }
+                return r;
             }
-            CurPrompt::SaveChanges => {
-                if let HandleRes::Exit(_) = self.prompt.handle_event(event, &mut ()) {
+            CurPrompt::Filter => {
+                let r = self.prompt.handle_event(event, &mut ());
+                if let HandleRes::Exit(apply) = r {
                     self.cur_prompt = CurPrompt::None;
+                    if apply {
+                        self.filter.name = self.prompt.fields[0].1.as_str().trim().to_string();
+                        self.filter.guests = self.prompt.fields[1].1.as_str() == "true";
+                        self.filter.deactivated = self.prompt.fields[2].1.as_str() == "true";
+                        self.filter.shadow_banned = self.prompt.fields[3].1.as_str() == "true";
+                        self.reload(&mut state.backend);
+                    }
                     return HandleRes::ReDraw;
                 }
+                return r;
             }
-            _ => {}
-        };
+            CurPrompt::Confirm(ref action) => {
+                let r = self.prompt.handle_event(event, &mut ());
+                if let HandleRes::Exit(confirmed) = r {
+                    if confirmed {
+                        let action = match self.confirmed_action(action) {
+                            Ok(a) => a,
+                            Err(e) => {
+                                self.prompt.error = e;
+                                return HandleRes::ReDraw;
+                            }
+                        };
+                        let targets = self
+                            .confirm_targets
+                            .drain(..)
+                            .map(|y| (y, self.user_table.rows[y][0].as_str().to_string()))
+                            .collect();
+                        self.bulk = Some((action, Batch::new(targets)));
+                    }
+                    self.cur_prompt = CurPrompt::None;
+                    return HandleRes::ReDraw;
+                }
+                return r;
+            }
+            CurPrompt::None => {}
+        }
 
         let key = match event {
             Event::Key(k) => k,
             _ => return HandleRes::Ignored,
         };
 
-        // Pass keystrokes to editable widget
-        if let Some(i) = self.editing_item() {
-            let r = i.handle_event(&key);
-            if matches!(r, HandleRes::ReDraw | HandleRes::Handled) {
-                return r;
+        if self.bulk.is_some() {
+            // Only allow stopping the current action
+            if let KeyCode::Esc = key.code {
+                self.finish_bulk();
+                return HandleRes::ReDraw;
             }
+            return HandleRes::Handled;
         }
 
-        let (val, amount, max) = match key.code {
-            KeyCode::Down => (&mut self.focus_y, 1, self.user_list.len()),
-            KeyCode::Up => (&mut self.focus_y, -1, self.user_list.len()),
-            KeyCode::Right => (&mut self.focus_x, 1, USER_COLUMNS.len()),
-            KeyCode::Left => (&mut self.focus_x, -1, USER_COLUMNS.len()),
-            KeyCode::PageDown => (&mut self.focus_y, 5, self.user_list.len()),
-            KeyCode::PageUp => (&mut self.focus_y, -5, self.user_list.len()),
-            KeyCode::F(5) => {
-                self.focus_y = 0;
-                self.user_list.clear();
+        let r = self.user_table.handle_event(key);
+        if let Some((x, y)) = self.user_table.take_committed() {
+            self.apply_edit(x, y, &mut state.backend);
+        }
+        if !matches!(r, HandleRes::Ignored) {
+            // Fetch more users when reaching the end of the list
+            if self.user_table.focus_y + 1 >= self.user_table.len() {
                 if let Err(e) = self.load_next_chunk(&mut state.backend) {
-                    self.prompt.clear();
-                    self.prompt.error.push_str(e.as_str());
-                    self.prompt.true_button.push_str("Ok");
+                    self.prompt.error_notice(e.as_str());
                     self.cur_prompt = CurPrompt::Notice;
+                    return HandleRes::ReDraw;
                 }
-                return HandleRes::ReDraw;
             }
-            KeyCode::Enter => {
-                if self.editing {
-                    self.editing = false;
-                } else if let Some(i) = self.cur_item() {
-                    if !i.is_editable() {
-                        return HandleRes::Handled;
-                    } else {
-                        self.editing = true;
-                    }
+            return r;
+        }
+
+        if let Some(action) = BulkAction::from_key(key.code) {
+            let selection = self.user_table.selection();
+            if selection.is_empty() {
+                return HandleRes::Handled;
+            }
+            self.confirm(action, selection, &mut state.backend);
+            return HandleRes::ReDraw;
+        }
+
+        match key.code {
+            KeyCode::F(5) => {
+                self.reload(&mut state.backend);
+                HandleRes::ReDraw
+            }
+            KeyCode::Char('i') => {
+                self.open_sub_view(Box::new(UserImportView::new()), state);
+                HandleRes::ReDraw
+            }
+            KeyCode::Char('D') => {
+                if let Some(user_id) = self.cur_user_id() {
+                    self.open_sub_view(Box::new(UserDevicesView::new(&user_id)), state);
                 }
-                return HandleRes::ReDraw;
+                HandleRes::ReDraw
             }
-            KeyCode::Esc if self.editing => {
-                self.editing = false;
-                if let Some(s) = self.editing_item() {
-                    s.restore_orig();
+            KeyCode::Char('j') => {
+                if let Some(user_id) = self.cur_user_id() {
+                    self.open_sub_view(Box::new(UserRoomsView::new(&user_id)), state);
                 }
-                return HandleRes::ReDraw;
+                HandleRes::ReDraw
             }
-            _ => return HandleRes::Ignored,
+            KeyCode::Char('u') => {
+                if let Some(user_id) = self.cur_user_id() {
+                    self.open_sub_view(Box::new(UserMediaView::new(&user_id)), state);
+                }
+                HandleRes::ReDraw
+            }
+            KeyCode::Char('w') => {
+                if let Some(user_id) = self.cur_user_id() {
+                    self.open_sub_view(Box::new(UserWhoisView::new(&user_id)), state);
+                }
+                HandleRes::ReDraw
+            }
+            KeyCode::Char('n') => {
+                let targets: Vec<String> = self
+                    .user_table
+                    .selection()
+                    .into_iter()
+                    .map(|y| self.user_table.rows[y][0].as_str().to_string())
+                    .collect();
+                if !targets.is_empty() {
+                    let view = ServerNoticeView::new(NoticeTargets::Users(targets));
+                    self.open_sub_view(Box::new(view), state);
+                }
+                HandleRes::ReDraw
+            }
+            KeyCode::Char('N') => {
+                let view = ServerNoticeView::new(NoticeTargets::AllLocal);
+                self.open_sub_view(Box::new(view), state);
+                HandleRes::ReDraw
+            }
+            KeyCode::Char('/') => {
+                self.prompt.clear();
+                self.prompt.msg.push_str("Filter users");
+                self.prompt.fields.push((
+                    "Name contains".into(),
+                    Editable::string(self.filter.name.as_str()),
+                ));
+                self.prompt
+                    .fields
+                    .push(("Guests".into(), Editable::bool(self.filter.guests)));
+                self.prompt.fields.push((
+                    "Deactivated".into(),
+                    Editable::bool(self.filter.deactivated),
+                ));
+                self.prompt.fields.push((
+                    "Only shadow-banned".into(),
+                    Editable::bool(self.filter.shadow_banned),
+                ));
+                self.prompt.true_button.push_str("Apply");
+                self.prompt.false_button.push_str("Cancel");
+                self.cur_prompt = CurPrompt::Filter;
+                HandleRes::ReDraw
+            }
+            KeyCode::Char('a') => {
+                // Selection applies to every user matching the filter
+                while let SyncState::Some = self.sync_state {
+                    if let Err(e) = self.load_next_chunk(&mut state.backend) {
+                        self.prompt.error_notice(e.as_str());
+                        self.cur_prompt = CurPrompt::Notice;
+                        return HandleRes::ReDraw;
+                    }
+                }
+                let all_selected = self.user_table.num_selected() == self.user_table.len();
+                self.user_table.select_all(!all_selected);
+                HandleRes::ReDraw
+            }
+            _ => HandleRes::Ignored,
+        }
+    }
+
+    fn tick(&mut self, state: &mut State) -> HandleRes {
+        if let Some(v) = self.sub_view.as_mut() {
+            return v.tick(state);
+        }
+        let (action, batch) = match self.bulk.as_mut() {
+            Some(b) => b,
+            None => return HandleRes::Ignored,
         };
+        let rows = &mut self.user_table.rows;
+        batch.step(|(y, user_id)| {
+            action.apply(&mut state.backend, user_id.as_str())?;
+            action.update_row(&mut rows[*y]);
+            Ok(())
+        });
+        if batch.is_done() {
+            self.finish_bulk();
+        }
+        HandleRes::ReDraw
+    }
 
-        let old = *val;
-        apply_offset(val, amount, max);
-        let new = *val;
-        // Disable edit mode if we landed on non editable field
-        if let Some(i) = self.cur_item() {
-            if !i.is_editable() && self.editing {
-                self.editing = false;
-            }
+    fn hints(&self) -> Vec<(&'static str, &'static str)> {
+        if let Some(v) = self.sub_view.as_ref() {
+            return v.hints();
         }
-        if old != new {
-            return HandleRes::ReDraw;
-        } else {
-            return HandleRes::Handled;
+        if self.bulk.is_some() {
+            return vec![("Esc", "Stop")];
         }
+        vec![
+            ("Enter", "Edit"),
+            ("Space", "Select"),
+            ("a", "Select all"),
+            ("/", "Filter"),
+            ("d/r", "Deactivate/Reactivate"),
+            ("m/M", "Make/Revoke admin"),
+            ("s/S", "Shadow-ban/Lift"),
+            ("l", "Logout devices"),
+            ("R", "Reset rate limits"),
+            ("D", "Devices"),
+            ("j", "Joined rooms"),
+            ("u", "Media"),
+            ("w", "Whois"),
+            ("n/N", "Notice selection/everyone"),
+            ("i", "Import users"),
+            ("F5", "Reload"),
+        ]
     }
 }
 
 impl UsersView {
-    pub fn cur_item(&mut self) -> Option<&mut Editable> {
-        if self.user_list.is_empty() {
-            return None;
-        }
-        if self.focus_y >= self.user_list.len() {
-            self.focus_y = 0;
-        }
-        let row = unsafe { self.user_list.get_unchecked_mut(self.focus_y) };
-        if self.focus_x >= row.len() {
-            self.focus_x = 0;
-        }
-        Some(&mut row[self.focus_x])
+    fn open_sub_view(&mut self, mut view: Box<dyn ViewImpl<State>>, state: &mut State) {
+        view.enter_view(state);
+        self.sub_view = Some(view);
+    }
+
+    /// Id of the user in the focused row
+    fn cur_user_id(&self) -> Option<String> {
+        self.user_table
+            .cur_row()
+            .map(|y| self.user_table.rows[y][0].as_str().to_string())
     }
 
-    pub fn editing_item(&mut self) -> Option<&mut Editable> {
-        if !self.editing {
-            return None;
+    /// Clears the list and fetches the first chunk of users again
+    fn reload(&mut self, synapse: &mut Synapse) {
+        self.user_table.clear();
+        self.sync_state = SyncState::Some;
+        if let Err(e) = self.load_next_chunk(synapse) {
+            self.prompt.error_notice(e.as_str());
+            self.cur_prompt = CurPrompt::Notice;
         }
-        self.cur_item()
     }
 
     fn load_next_chunk(&mut self, synapse: &mut Synapse) -> Result<usize, String> {
         if let SyncState::Max = self.sync_state {
             return Ok(0);
         }
-        let mut l = synapse.list_users(self.user_list.len(), 32)?;
+        let mut l = synapse.list_users(self.user_table.len(), PAGE_SIZE, &self.filter)?;
         let num_received = l.len();
+        let mut hit_end = num_received < PAGE_SIZE;
         for u in l.drain(..) {
-            self.user_list.push([
+            // Shadow-banned users come first, anything after is filtered out
+            if self.filter.shadow_banned && !u.shadow_banned {
+                hit_end = true;
+                break;
+            }
+            self.user_table.push(vec![
                 Editable::ro_string(u.name.as_str()),
                 Editable::string(u.displayname.as_str()),
                 Editable::bool(u.admin),
-                Editable::bool(u.is_guest),
+                Editable::ro_bool(u.is_guest),
                 Editable::bool(!u.deactivated),
+                Editable::bool(u.shadow_banned),
             ]);
         }
         // We got less than what we queried for, we hit the end
-        if num_received < 32 {
+        if hit_end {
             self.sync_state = SyncState::Max;
         } else {
             self.sync_state = SyncState::Some;
         }
         Ok(num_received)
     }
+
+    /// Applies a value the user just modified in the table
+    fn apply_edit(&mut self, x: usize, y: usize, synapse: &mut Synapse) {
+        let user_id = self.user_table.rows[y][0].as_str().to_string();
+        let cell = &mut self.user_table.rows[y][x];
+        let r = match x {
+            NAME_COLUMN => synapse.modify_user(
+                user_id.as_str(),
+                &UserModifyV2 {
+                    displayname: Some(cell.as_str().to_string()),
+                    ..Default::default()
+                },
+            ),
+            ADMIN_COLUMN => synapse.set_admin(user_id.as_str(), cell.as_str() == "true"),
+            SHADOW_BANNED_COLUMN => {
+                synapse.set_shadow_banned(user_id.as_str(), cell.as_str() == "true")
+            }
+            ACTIVE_COLUMN => {
+                // (De)activation goes through its confirmation prompt
+                let active = cell.as_str() == "true";
+                cell.restore_orig();
+                let action = if active {
+                    BulkAction::Reactivate(None)
+                } else {
+                    BulkAction::Deactivate(false)
+                };
+                self.confirm(action, vec![y], synapse);
+                return;
+            }
+            _ => return,
+        };
+        match r {
+            Ok(_) => cell.forget_orig(),
+            Err(e) => {
+                cell.restore_orig();
+                self.prompt.error_notice(e.as_str());
+                self.cur_prompt = CurPrompt::Notice;
+            }
+        }
+    }
+
+    /// Asks the user to confirm applying `action` to the `targets` rows
+    fn confirm(&mut self, action: BulkAction, targets: Vec<usize>, synapse: &mut Synapse) {
+        self.prompt.clear();
+        self.prompt.msg = format!("{} {} user(s) ?", action.desc(), targets.len());
+        for y in targets.iter().take(MAX_LISTED_USERS) {
+            self.prompt.msg.push_str("\n  ");
+            self.prompt
+                .msg
+                .push_str(self.user_table.rows[*y][0].as_str());
+        }
+        if targets.len() > MAX_LISTED_USERS {
+            self.prompt.msg.push_str("\n  ...");
+        }
+        match action {
+            BulkAction::Deactivate(_) => {
+                self.prompt.msg.push_str(&format!(
+                    "\nDeactivation cannot be undone, type {} to confirm",
+                    if targets.len() == 1 {
+                        "the user id"
+                    } else {
+                        "the number of users"
+                    }
+                ));
+                self.prompt
+                    .fields
+                    .push(("Erase data (GDPR)".into(), Editable::bool(false)));
+                self.prompt
+                    .fields
+                    .push(("Confirm".into(), Editable::string("")));
+            }
+            BulkAction::Reactivate(_) => match synapse.password_login_enabled() {
+                Ok(true) => {
+                    self.prompt
+                        .msg
+                        .push_str("\nThe server uses local passwords, a new one must be set");
+                    if targets.len() > 1 {
+                        self.prompt
+                            .msg
+                            .push_str("\nAll the users will get the same password");
+                    }
+                    self.prompt
+                        .fields
+                        .push(("Password".into(), Editable::string("")));
+                }
+                Ok(false) => {}
+                Err(e) => {
+                    self.prompt.error_notice(e.as_str());
+                    self.cur_prompt = CurPrompt::Notice;
+                    return;
+                }
+            },
+            _ => {}
+        }
+        self.prompt.true_button.push_str("Confirm");
+        self.prompt.false_button.push_str("Cancel");
+        self.confirm_targets = targets;
+        self.cur_prompt = CurPrompt::Confirm(action);
+    }
+
+    /// Validates the confirmation prompt and returns the action to apply
+    fn confirmed_action(&self, action: &BulkAction) -> Result<BulkAction, String> {
+        match action {
+            BulkAction::Deactivate(_) => {
+                let erase = self.prompt.fields[0].1.as_str() == "true";
+                let typed = self.prompt.fields[1].1.as_str().trim();
+                let expected = if self.confirm_targets.len() == 1 {
+                    self.user_table.rows[self.confirm_targets[0]][0]
+                        .as_str()
+                        .to_string()
+                } else {
+                    self.confirm_targets.len().to_string()
+                };
+                if typed != expected {
+                    return Err(format!("Type '{}' to confirm", expected));
+                }
+                Ok(BulkAction::Deactivate(erase))
+            }
+            BulkAction::Reactivate(_) => match self.prompt.fields.first() {
+                Some((_, password)) if password.as_str().is_empty() => {
+                    Err("A password is required".into())
+                }
+                Some((_, password)) => {
+                    Ok(BulkAction::Reactivate(Some(password.as_str().to_string())))
+                }
+                None => Ok(BulkAction::Reactivate(None)),
+            },
+            _ => Ok(action.clone()),
+        }
+    }
+
+    /// Displays the results of the current bulk action
+    fn finish_bulk(&mut self) {
+        let (action, batch) = match self.bulk.take() {
+            Some(b) => b,
+            None => return,
+        };
+        self.prompt.clear();
+        self.prompt.msg = format!("{} : {}", action.desc(), batch.progress());
+        if !batch.is_done() {
+            self.prompt.msg.push_str(" (stopped)");
+        }
+        for (idx, e) in batch.failures.iter() {
+            self.prompt
+                .error
+                .push_str(&format!("{} : {}\n", batch.items[*idx].1, e));
+        }
+        self.prompt.true_button.push_str("Ok");
+        self.cur_prompt = CurPrompt::Notice;
+    }
+
+    fn status_line(&self) -> String {
+        if let Some((action, batch)) = self.bulk.as_ref() {
+            return format!("{} : {}", action.desc(), batch.progress());
+        }
+        let mut r = format!(
+            "{}{} users, {} selected",
+            self.user_table.len(),
+            if let SyncState::Some = self.sync_state {
+                "+"
+            } else {
+                ""
+            },
+            self.user_table.num_selected()
+        );
+        if !self.filter.name.is_empty() {
+            r.push_str(&format!(" | name contains '{}'", self.filter.name));
+        }
+        if self.filter.guests {
+            r.push_str(" | with guests");
+        }
+        if self.filter.deactivated {
+            r.push_str(" | with deactivated");
+        }
+        if self.filter.shadow_banned {
+            r.push_str(" | only shadow-banned, deactivated included");
+        }
+        r
+    }
 }